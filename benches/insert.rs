@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use floaty_hash::FloatHashSet;
+
+fn values() -> Vec<f32> {
+    (0..10_000u16)
+        .map(|i| f32::from(i % 4_000) * 0.37)
+        .collect()
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let values = values();
+    let mut group = c.benchmark_group("insert");
+
+    group.bench_function("single", |b| {
+        b.iter(|| {
+            let mut set = FloatHashSet::with_fixed_seed(0.001, 0);
+            for val in &values {
+                set.insert(black_box(*val));
+            }
+            set
+        });
+    });
+    group.bench_function("insert_many", |b| {
+        b.iter(|| {
+            let mut set = FloatHashSet::with_fixed_seed(0.001, 0);
+            set.insert_many(black_box(&values));
+            set
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use floaty_hash::FloatHashSet;
+
+const TOLERANCE: f32 = 0.001;
+
+/// What a `FloatHashSet` replaces: a `Vec` of representatives, scanned in
+/// full for anything within tolerance.
+fn linear_contains(values: &[f32], query: f32) -> bool {
+    values
+        .iter()
+        .any(|val| val.to_bits() == query.to_bits() || (val - query).abs() <= TOLERANCE)
+}
+
+fn bench_linear_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("linear_scan");
+    for len in [4u16, 16, 64, 256, 1024, 4096] {
+        let values: Vec<f32> = (0..len).map(|i| f32::from(i) * 0.37).collect();
+        // Half hits, half misses
+        let probes: Vec<f32> = (0..256u16)
+            .map(|i| f32::from(i % len) * 0.37 + f32::from(i % 2) * 0.1)
+            .collect();
+        let mut set = FloatHashSet::new(TOLERANCE);
+        set.extend(values.iter().copied());
+
+        group.bench_with_input(BenchmarkId::new("vec", len), &values, |b, values| {
+            b.iter(|| {
+                probes
+                    .iter()
+                    .filter(|val| linear_contains(values, black_box(**val)))
+                    .count()
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("hashed", len), &set, |b, set| {
+            b.iter(|| {
+                probes
+                    .iter()
+                    .filter(|val| set.contains(black_box(**val)))
+                    .count()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_linear_scan);
+criterion_main!(benches);
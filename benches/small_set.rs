@@ -0,0 +1,35 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use floaty_hash::{FloatHashSet, SmallFloatSet};
+
+fn bench_small_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small_set");
+    for len in [4u8, 8, 15, 32, 63] {
+        let values: Vec<f32> = (0..len).map(|i| f32::from(i) * 0.37).collect();
+        let probes: Vec<f32> = (0..len * 2).map(|i| f32::from(i) * 0.185).collect();
+
+        group.bench_with_input(BenchmarkId::new("small", len), &values, |b, values| {
+            b.iter(|| {
+                let mut set = SmallFloatSet::new(0.001);
+                set.extend(values.iter().copied());
+                probes
+                    .iter()
+                    .filter(|val| set.contains(black_box(**val)))
+                    .count()
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("hashed", len), &values, |b, values| {
+            b.iter(|| {
+                let mut set = FloatHashSet::new(0.001);
+                set.extend(values.iter().copied());
+                probes
+                    .iter()
+                    .filter(|val| set.contains(black_box(**val)))
+                    .count()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_small_set);
+criterion_main!(benches);
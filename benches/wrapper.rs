@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use floaty_hash::{CachedF32Wrapper, F32Wrapper};
+
+fn values() -> Vec<f32> {
+    (0..10_000u16).map(|i| f32::from(i) * 0.37).collect()
+}
+
+fn bench_wrapper(c: &mut Criterion) {
+    let values = values();
+    let set: HashSet<F32Wrapper> = values.iter().copied().map(F32Wrapper::new).collect();
+    let mut group = c.benchmark_group("wrapper");
+
+    group.bench_function("bulk_insert", |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .map(|val| F32Wrapper::new(black_box(*val)))
+                .collect::<HashSet<_>>()
+        });
+    });
+    group.bench_function("bulk_lookup", |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .filter(|val| set.contains(&F32Wrapper::new(black_box(**val))))
+                .count()
+        });
+    });
+    group.bench_function("eq", |b| {
+        b.iter(|| {
+            values
+                .windows(2)
+                .filter(|pair| F32Wrapper::new(black_box(pair[0])) == F32Wrapper::new(pair[1]))
+                .count()
+        });
+    });
+    // Integer-valued floats go through the same grid as any other value;
+    // these two show whether that costs them anything
+    let integers: Vec<f32> = (0..10_000u16).map(f32::from).collect();
+    group.bench_function("hash_integers", |b| {
+        b.iter(|| {
+            integers
+                .iter()
+                .map(|val| F32Wrapper::new(black_box(*val)).hash_value())
+                .fold(0, u64::wrapping_add)
+        });
+    });
+    group.bench_function("hash_fractions", |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .map(|val| F32Wrapper::new(black_box(*val)).hash_value())
+                .fold(0, u64::wrapping_add)
+        });
+    });
+    // The same few keys looked up again and again, hashing each every time
+    let queries: Vec<F32Wrapper> = values
+        .iter()
+        .step_by(100)
+        .copied()
+        .map(F32Wrapper::new)
+        .collect();
+    let cached_set: HashSet<CachedF32Wrapper> =
+        set.iter().copied().map(CachedF32Wrapper::new).collect();
+    let cached_queries: Vec<CachedF32Wrapper> =
+        queries.iter().copied().map(CachedF32Wrapper::new).collect();
+    group.bench_function("repeated_lookup", |b| {
+        b.iter(|| {
+            (0..100)
+                .flat_map(|_| &queries)
+                .filter(|query| set.contains(black_box(*query)))
+                .count()
+        });
+    });
+    group.bench_function("repeated_lookup_cached", |b| {
+        b.iter(|| {
+            (0..100)
+                .flat_map(|_| &cached_queries)
+                .filter(|query| cached_set.contains(black_box(*query)))
+                .count()
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_wrapper);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+use floaty_hash::weld_vertices;
+
+// A cube exported one face at a time, so every corner shows up three times
+// with a little floating point noise
+fn main() {
+    let mut vertices = Vec::new();
+    for axis in 0..3 {
+        for side in [0.0, 1.0] {
+            for (u, v) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+                let mut vertex = [0.0f32; 3];
+                vertex[axis] = side + 1e-6;
+                vertex[(axis + 1) % 3] = u - 1e-6;
+                vertex[(axis + 2) % 3] = v;
+                vertices.push(vertex);
+            }
+        }
+    }
+
+    let (unique, indices) = weld_vertices(&vertices, 0.0001);
+    println!(
+        "Welded {} vertices down to {}",
+        vertices.len(),
+        unique.len()
+    );
+    println!("Index buffer: {indices:?}");
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::F32Wrapper;
+
+/// Why a checked aggregation gave up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AggregateError {
+    /// The element at `index` was NaN or infinite.
+    NonFiniteElement { index: usize },
+    /// Adding the element at `index` overflowed the running total.
+    NonFiniteTotal { index: usize },
+}
+
+impl fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateError::NonFiniteElement { index } => {
+                write!(f, "element {index} is NaN or infinite")
+            }
+            AggregateError::NonFiniteTotal { index } => {
+                write!(f, "running total became non-finite at element {index}")
+            }
+        }
+    }
+}
+
+impl Error for AggregateError {}
+
+/// Sums `values`, failing on the first NaN or infinity instead of letting it
+/// silently contaminate the result.
+pub fn checked_sum<I>(values: I) -> Result<F32Wrapper, AggregateError>
+where
+    I: IntoIterator<Item = F32Wrapper>,
+{
+    let mut total = 0.0f32;
+    for (index, val) in values.into_iter().enumerate() {
+        if !val.value().is_finite() {
+            return Err(AggregateError::NonFiniteElement { index });
+        }
+        total += val.value();
+        if !total.is_finite() {
+            return Err(AggregateError::NonFiniteTotal { index });
+        }
+    }
+    Ok(F32Wrapper::new(total))
+}
+
+/// Summary statistics over a collection of floats; see
+/// [`crate::FloatHashSet::statistics`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FloatStats {
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// The population standard deviation, i.e. dividing by `count`.
+    pub std_dev: f32,
+}
+
+impl FloatStats {
+    /// Accumulates with Welford's algorithm in `f64`, which doesn't lose
+    /// precision to catastrophic cancellation like summing squares does.
+    /// With no values everything but `count` is NaN.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub(crate) fn from_values<I: IntoIterator<Item = f32>>(values: I) -> Self {
+        let mut count = 0usize;
+        let (mut min, mut max) = (f32::NAN, f32::NAN);
+        let (mut mean, mut sum_sq_diffs) = (0.0f64, 0.0f64);
+        for val in values {
+            count += 1;
+            min = min.min(val);
+            max = max.max(val);
+            let delta = f64::from(val) - mean;
+            mean += delta / count as f64;
+            sum_sq_diffs += delta * (f64::from(val) - mean);
+        }
+        if count == 0 {
+            return FloatStats {
+                count,
+                min,
+                max,
+                mean: f32::NAN,
+                std_dev: f32::NAN,
+            };
+        }
+        FloatStats {
+            count,
+            min,
+            max,
+            mean: mean as f32,
+            std_dev: (sum_sq_diffs / count as f64).sqrt() as f32,
+        }
+    }
+}
+
+#[test]
+fn it_sums_clean_data() {
+    let values = [1.0, 2.5, -0.5].map(F32Wrapper::new);
+
+    assert!(checked_sum(values) == Ok(F32Wrapper::new(3.0)));
+}
+
+#[test]
+fn it_rejects_nan_elements() {
+    let values = [1.0, f32::NAN, 2.0].map(F32Wrapper::new);
+
+    assert!(checked_sum(values) == Err(AggregateError::NonFiniteElement { index: 1 }));
+}
+
+#[test]
+fn it_rejects_overflowing_totals() {
+    let values = [f32::MAX, f32::MAX].map(F32Wrapper::new);
+
+    assert!(checked_sum(values) == Err(AggregateError::NonFiniteTotal { index: 1 }));
+}
+
+#[test]
+fn it_computes_summary_statistics() {
+    let stats = FloatStats::from_values([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+
+    assert!(stats.count == 8);
+    assert!(stats.min == 2.0 && stats.max == 9.0);
+    assert!(stats.mean == 5.0);
+    assert!(stats.std_dev == 2.0);
+}
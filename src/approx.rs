@@ -0,0 +1,82 @@
+#[cfg(test)]
+use crate::F32Wrapper;
+use crate::{ComparisonMode, F32_ERROR_TOLERANCE};
+
+/// An `f32` compared by tolerance alone: equal to anything within its
+/// tolerance, or matching under either side's mode if the two differ.
+///
+/// That "equality" isn't transitive, so this implements only `PartialEq`.
+/// With no `Eq` or `Hash` it can't become a `HashSet` or `HashMap` key,
+/// where it would break the lookups. Use [`crate::F32Wrapper`] for keys,
+/// which is equal by grid cell instead:
+///
+/// ```compile_fail
+/// use std::collections::HashSet;
+/// use floaty_hash::ApproxF32;
+///
+/// let mut set = HashSet::new();
+/// set.insert(ApproxF32::new(1.0));
+/// ```
+///
+/// ```
+/// use std::collections::HashSet;
+/// use floaty_hash::{ApproxF32, F32Wrapper};
+///
+/// let mut set = HashSet::new();
+/// set.insert(F32Wrapper::new(1.0));
+///
+/// // 0.5 and 1.4 are in different cells, but within a tolerance of 1
+/// assert!(ApproxF32::with_tolerance(0.5, 1.0) == ApproxF32::with_tolerance(1.4, 1.0));
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct ApproxF32 {
+    inner: f32,
+    mode: ComparisonMode,
+}
+
+impl ApproxF32 {
+    pub fn new(val: f32) -> Self {
+        Self::with_tolerance(val, F32_ERROR_TOLERANCE)
+    }
+
+    pub fn with_tolerance(val: f32, tolerance: f32) -> Self {
+        Self::with_mode(val, ComparisonMode::Absolute(tolerance))
+    }
+
+    pub fn with_mode(val: f32, mode: ComparisonMode) -> Self {
+        ApproxF32 { inner: val, mode }
+    }
+
+    pub fn value(self) -> f32 {
+        self.inner
+    }
+
+    pub fn mode(self) -> ComparisonMode {
+        self.mode
+    }
+}
+
+impl PartialEq for ApproxF32 {
+    fn eq(&self, other: &Self) -> bool {
+        // Matching under either mode keeps this symmetric, and for two
+        // absolute tolerances amounts to comparing at the wider one
+        self.mode.matches(self.inner, other.inner) || other.mode.matches(self.inner, other.inner)
+    }
+}
+
+impl PartialEq<f32> for ApproxF32 {
+    fn eq(&self, other: &f32) -> bool {
+        self.mode.matches(self.inner, *other)
+    }
+}
+
+#[test]
+fn it_compares_within_tolerance_across_cell_edges() {
+    let a = ApproxF32::with_tolerance(0.49, 1.0);
+    let b = ApproxF32::with_tolerance(0.51, 1.0);
+
+    assert!(a == b);
+    assert!(F32Wrapper::with_tolerance(0.49, 1.0) != F32Wrapper::with_tolerance(0.51, 1.0));
+    assert!(a != ApproxF32::with_tolerance(1.6, 1.0));
+    assert!(ApproxF32::with_tolerance(1.0, 0.1) == ApproxF32::with_tolerance(1.05, 0.01));
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use crate::mode::validated_tolerance;
+use crate::{grid, ToleranceError, F32_ERROR_TOLERANCE};
+
+/// A fixed-size array of floats (a point, a color, ...) that hashes and
+/// compares tolerantly, component by component.
+///
+/// Two arrays are equal iff they have the same tolerance and every pair of
+/// components snaps to the same grid cell, which is exactly what's hashed.
+/// As with [`crate::F32Wrapper`], NaNs match each other, and components
+/// within tolerance but either side of a cell edge don't.
+#[derive(Debug, Copy, Clone)]
+pub struct FloatArrayWrapper<const N: usize> {
+    inner: [f32; N],
+    tolerance: f32,
+}
+
+impl<const N: usize> FloatArrayWrapper<N> {
+    pub fn new(val: [f32; N]) -> Self {
+        Self::with_tolerance(val, F32_ERROR_TOLERANCE)
+    }
+
+    /// `tolerance` must be finite and not negative, which debug builds
+    /// assert.
+    pub fn with_tolerance(val: [f32; N], tolerance: f32) -> Self {
+        debug_assert!(
+            validated_tolerance(tolerance).is_ok(),
+            "invalid tolerance {tolerance}: must be finite and at least zero"
+        );
+        FloatArrayWrapper {
+            inner: val,
+            tolerance,
+        }
+    }
+
+    /// Like [`FloatArrayWrapper::with_tolerance`], but returns an error for
+    /// a negative, NaN, or infinite tolerance in every build.
+    pub fn try_with_tolerance(val: [f32; N], tolerance: f32) -> Result<Self, ToleranceError> {
+        Ok(Self::with_tolerance(val, validated_tolerance(tolerance)?))
+    }
+
+    pub fn value(self) -> [f32; N] {
+        self.inner
+    }
+
+    pub fn tolerance(self) -> f32 {
+        self.tolerance
+    }
+}
+
+impl<const N: usize> PartialEq for FloatArrayWrapper<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.tolerance == other.tolerance
+            && self.inner.iter().zip(&other.inner).all(|(a, b)| {
+                grid::cell_or_nan(*a, self.tolerance) == grid::cell_or_nan(*b, self.tolerance)
+            })
+    }
+}
+
+impl<const N: usize> Eq for FloatArrayWrapper<N> {}
+
+impl<const N: usize> Hash for FloatArrayWrapper<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for component in &self.inner {
+            state.write_u64(grid::mix(grid::cell_or_nan(*component, self.tolerance)));
+        }
+    }
+}
+
+#[test]
+fn it_treats_close_points_as_the_same() {
+    let mut set = HashSet::new();
+    set.insert(FloatArrayWrapper::with_tolerance([1.0, 2.0, -3.0], 0.001));
+    set.insert(FloatArrayWrapper::with_tolerance(
+        [1.0001, 1.9999, -3.0002],
+        0.001,
+    ));
+    set.insert(FloatArrayWrapper::with_tolerance([1.0, 2.0, 3.0], 0.001));
+
+    assert!(set.len() == 2);
+}
+
+#[test]
+fn it_hashes_equal_points_alike_even_across_cell_edges() {
+    use std::collections::hash_map::DefaultHasher;
+
+    let hash_of = |point: &FloatArrayWrapper<2>| {
+        let mut hasher = DefaultHasher::new();
+        point.hash(&mut hasher);
+        hasher.finish()
+    };
+    let straddling = (
+        FloatArrayWrapper::with_tolerance([1.0, 0.000149], 0.0001),
+        FloatArrayWrapper::with_tolerance([1.0, 0.000151], 0.0001),
+    );
+    assert!(straddling.0 != straddling.1);
+
+    let points = [
+        straddling.0,
+        straddling.1,
+        FloatArrayWrapper::with_tolerance([1.00001, 0.00012], 0.0001),
+        FloatArrayWrapper::with_tolerance([1.0, 0.00012], 0.001),
+        FloatArrayWrapper::with_tolerance([f32::NAN, -0.0], 0.0001),
+        FloatArrayWrapper::with_tolerance([-f32::NAN, 0.0], 0.0001),
+    ];
+    for a in &points {
+        for b in &points {
+            assert!(a != b || hash_of(a) == hash_of(b), "{a:?} vs {b:?}");
+        }
+    }
+    assert!(points[0] == points[2] && points[2] != points[3]);
+    assert!(points[4] == points[5]);
+}
+
+#[test]
+fn it_rejects_negative_and_nan_tolerances() {
+    assert!(FloatArrayWrapper::try_with_tolerance([1.0, 2.0], -0.1).is_err());
+    assert!(FloatArrayWrapper::try_with_tolerance([1.0, 2.0], f32::NAN).is_err());
+    assert!(FloatArrayWrapper::try_with_tolerance([1.0, 2.0], 0.0).is_ok());
+    assert!(
+        FloatArrayWrapper::try_with_tolerance([1.0, 2.0], 0.001)
+            .unwrap()
+            .tolerance()
+            == 0.001
+    );
+}
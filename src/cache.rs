@@ -0,0 +1,123 @@
+use crate::FloatHashMap;
+
+/// A bounded memoization cache for functions of an `f32`, where inputs within
+/// `tolerance` of a cached one share its result.
+///
+/// Once full, inserting a new key evicts the least recently used one. Lookups
+/// go through a [`FloatHashMap`], so they're as tolerant as its own; finding
+/// the entry to evict is a linear scan, which is fine at the few hundred
+/// entries caches like this usually hold.
+#[derive(Debug, Clone)]
+pub struct FloatCache<V> {
+    capacity: usize,
+    entries: FloatHashMap<(V, u64)>,
+    /// Bumped on every access, stamping entries with how recently they were
+    /// used.
+    clock: u64,
+}
+
+impl<V> FloatCache<V> {
+    /// Creates an empty cache holding at most `capacity` results.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0, as there'd be nowhere to keep a computed value.
+    pub fn new(tolerance: f32, capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "a FloatCache needs room for at least one entry"
+        );
+        FloatCache {
+            capacity,
+            entries: FloatHashMap::new(tolerance),
+            clock: 0,
+        }
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.entries.tolerance()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether a cached key lies within tolerance of `key`. Doesn't count as
+    /// a use.
+    pub fn contains(&self, key: f32) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// The result cached for a key within tolerance of `key`, or else
+    /// `compute()`'s, cached under `key` after evicting the least recently
+    /// used entry if the cache is full.
+    pub fn get_or_insert_with<C: FnOnce() -> V>(&mut self, key: f32, compute: C) -> &V {
+        self.clock += 1;
+        if !self.entries.contains_key(key) && self.entries.len() == self.capacity {
+            self.evict_least_recent();
+        }
+        let clock = self.clock;
+        let entry = self
+            .entries
+            .entry(key)
+            .or_insert_with(|| (compute(), clock));
+        entry.1 = clock;
+        &entry.0
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn evict_least_recent(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(key, _)| key);
+        if let Some(key) = oldest {
+            self.entries.remove(key);
+        }
+    }
+}
+
+#[test]
+fn it_hits_the_cache_for_a_nearby_key() {
+    let mut cache = FloatCache::new(0.001, 4);
+    let mut calls = 0;
+
+    let first = *cache.get_or_insert_with(2.0, || {
+        calls += 1;
+        4.0
+    });
+    let second = *cache.get_or_insert_with(2.0004, || {
+        calls += 1;
+        4.0016
+    });
+
+    assert!(first == 4.0 && second == 4.0);
+    assert!(calls == 1);
+    assert!(cache.len() == 1);
+}
+
+#[test]
+fn it_evicts_the_least_recently_used_key() {
+    let mut cache = FloatCache::new(0.001, 2);
+    cache.get_or_insert_with(1.0, || 1);
+    cache.get_or_insert_with(2.0, || 2);
+    // Touching 1.0 leaves 2.0 as the stalest
+    cache.get_or_insert_with(1.0001, || unreachable!());
+    cache.get_or_insert_with(3.0, || 3);
+
+    assert!(cache.len() == 2);
+    assert!(cache.contains(1.0) && cache.contains(3.0));
+    assert!(!cache.contains(2.0));
+}
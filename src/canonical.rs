@@ -0,0 +1,310 @@
+use std::hash::{Hash, Hasher};
+
+use crate::grid;
+use crate::mode::{ordered_bits, sign_and_exponent};
+
+/// Turns a float into the cell it hashes by. Two values are treated as the
+/// same exactly when they land in the same cell, so any implementation gives
+/// a sound `Eq` and `Hash`, as long as it's a pure function of `val`, and
+/// (for [`CanonicalF32`]) its own `PartialEq` only equates canonicalizers
+/// that bucket identically.
+///
+/// The built-in ones back [`crate::ComparisonMode`]'s variants. Implement it
+/// to plug in your own bucketing and wrap values in a [`CanonicalF32`].
+pub trait Canonicalizer {
+    fn canonicalize(&self, val: f32) -> i64;
+}
+
+/// Snaps onto a linear grid `tolerance` wide, as
+/// [`crate::ComparisonMode::Absolute`] does.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridCanonicalizer {
+    pub tolerance: f32,
+}
+
+impl Canonicalizer for GridCanonicalizer {
+    #[inline]
+    fn canonicalize(&self, val: f32) -> i64 {
+        grid::cell(f64::from(val), f64::from(self.tolerance))
+    }
+}
+
+/// Snaps `ln |val|` onto a linear grid `ln(1 + tolerance)` wide, so cells
+/// grow with magnitude, as [`crate::ComparisonMode::Relative`] does.
+/// Magnitudes below `f32::MIN_POSITIVE` share cell 0, matching the near-zero
+/// fallback in the comparison, and negative values get negated cells.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RelativeCanonicalizer {
+    pub tolerance: f32,
+}
+
+impl Canonicalizer for RelativeCanonicalizer {
+    fn canonicalize(&self, val: f32) -> i64 {
+        let magnitude = f64::from(val.abs());
+        let min_positive = f64::from(f32::MIN_POSITIVE);
+        if magnitude < min_positive {
+            return 0;
+        }
+        let cell = 1 + grid::cell(
+            (magnitude / min_positive).ln(),
+            f64::from(self.tolerance).ln_1p(),
+        );
+        if val.is_sign_negative() {
+            -cell
+        } else {
+            cell
+        }
+    }
+}
+
+/// Floors `ln |val|` onto a linear grid `ln(1 + ratio)` wide, so bucket `k`
+/// holds the magnitudes in `[(1 + ratio)^k, (1 + ratio)^(k + 1))`, as
+/// [`crate::ComparisonMode::LogBuckets`] does. Both zeros get cell 0 to
+/// themselves, and negative values get negated cells. A zero ratio gives
+/// every value a cell of its own.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LogCanonicalizer {
+    pub ratio: f32,
+}
+
+impl LogCanonicalizer {
+    /// Where bucket 0 (the one starting at `1.0`) lands, leaving room for
+    /// far more buckets either side than any ratio but a subnormal one needs.
+    const ORIGIN: i64 = 1 << 61;
+
+    /// The bucket index `k` of a nonzero magnitude, saturating well inside
+    /// the room around [`Self::ORIGIN`].
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn bucket(self, magnitude: f32) -> i64 {
+        let width = f64::from(self.ratio).ln_1p();
+        ((f64::from(magnitude).ln() / width).floor() as i64).clamp(-Self::ORIGIN + 1, Self::ORIGIN)
+    }
+}
+
+impl Canonicalizer for LogCanonicalizer {
+    fn canonicalize(&self, val: f32) -> i64 {
+        if self.ratio == 0.0 {
+            return ordered_bits(val);
+        }
+        let magnitude = val.abs();
+        if magnitude == 0.0 {
+            return 0;
+        }
+        let cell = Self::ORIGIN + self.bucket(magnitude);
+        if val.is_sign_negative() {
+            -cell
+        } else {
+            cell
+        }
+    }
+}
+
+/// Chops the monotonic bit ordering into runs of `ulps + 1`, as
+/// [`crate::ComparisonMode::Ulps`] does.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UlpCanonicalizer {
+    pub ulps: u32,
+}
+
+impl Canonicalizer for UlpCanonicalizer {
+    fn canonicalize(&self, val: f32) -> i64 {
+        ordered_bits(val).div_euclid(i64::from(self.ulps) + 1)
+    }
+}
+
+/// Just the sign and exponent bits, as [`crate::ComparisonMode::Magnitude`]
+/// does.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExponentCanonicalizer;
+
+impl Canonicalizer for ExponentCanonicalizer {
+    fn canonicalize(&self, val: f32) -> i64 {
+        i64::from(sign_and_exponent(val))
+    }
+}
+
+/// Buckets by which interval between a sorted list of `breakpoints` a value
+/// falls in, for domain-specific, nonuniform boundaries like calibration
+/// ranges. Each breakpoint opens the interval above it, so with breakpoints
+/// `[0.0, 10.0]` the cells are `(-inf, 0.0)`, `[0.0, 10.0)` and `[10.0, inf]`,
+/// numbered from 0. Both zeros fall in the same interval.
+///
+/// The breakpoints are borrowed, so wrappers stay `Copy` however many there
+/// are; keep them alive (and unchanged) as long as the wrappers.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BreakpointCanonicalizer<'a> {
+    breakpoints: &'a [f32],
+}
+
+impl<'a> BreakpointCanonicalizer<'a> {
+    /// `breakpoints` must be ascending and free of NaN, which debug builds
+    /// assert. Out of order, values would still get consistent cells, just
+    /// not the intended ones.
+    pub fn new(breakpoints: &'a [f32]) -> Self {
+        debug_assert!(
+            breakpoints.windows(2).all(|pair| pair[0] <= pair[1]),
+            "breakpoints must be sorted and free of NaN, got {breakpoints:?}"
+        );
+        BreakpointCanonicalizer { breakpoints }
+    }
+
+    pub fn breakpoints(&self) -> &'a [f32] {
+        self.breakpoints
+    }
+}
+
+impl Canonicalizer for BreakpointCanonicalizer<'_> {
+    #[allow(clippy::cast_possible_wrap)]
+    fn canonicalize(&self, val: f32) -> i64 {
+        self.breakpoints
+            .partition_point(|breakpoint| *breakpoint <= val) as i64
+    }
+}
+
+/// An `f32` that's equal to, and hashes like, every value its canonicalizer
+/// puts in the same cell. All NaNs are equal to each other and to nothing
+/// else, whatever the canonicalizer says.
+#[derive(Debug, Copy, Clone)]
+pub struct CanonicalF32<C> {
+    inner: f32,
+    canonicalizer: C,
+}
+
+impl<C: Canonicalizer> CanonicalF32<C> {
+    pub fn new(val: f32, canonicalizer: C) -> Self {
+        CanonicalF32 {
+            inner: val,
+            canonicalizer,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.inner
+    }
+
+    pub fn canonicalizer(&self) -> &C {
+        &self.canonicalizer
+    }
+}
+
+/// Like [`crate::F32Wrapper`], values under differently configured
+/// canonicalizers are never equal, even with identical bits, since they
+/// hash by different cells.
+impl<C: Canonicalizer + PartialEq> PartialEq for CanonicalF32<C> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.canonicalizer != other.canonicalizer {
+            return false;
+        }
+        if self.inner.to_bits() == other.inner.to_bits() {
+            return true;
+        }
+        if self.inner.is_nan() || other.inner.is_nan() {
+            return self.inner.is_nan() && other.inner.is_nan();
+        }
+        self.canonicalizer.canonicalize(self.inner) == self.canonicalizer.canonicalize(other.inner)
+    }
+}
+
+impl<C: Canonicalizer + PartialEq> Eq for CanonicalF32<C> {}
+
+impl<C: Canonicalizer> Hash for CanonicalF32<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let cell = if self.inner.is_nan() {
+            i64::MIN
+        } else {
+            self.canonicalizer.canonicalize(self.inner)
+        };
+        state.write_u64(grid::mix(cell));
+    }
+}
+
+#[test]
+fn it_buckets_with_a_custom_canonicalizer() {
+    use std::collections::HashSet;
+
+    /// Buckets by whole number, rounding down.
+    #[derive(Copy, Clone, PartialEq)]
+    struct Floor;
+
+    impl Canonicalizer for Floor {
+        #[allow(clippy::cast_possible_truncation)]
+        fn canonicalize(&self, val: f32) -> i64 {
+            val.floor() as i64
+        }
+    }
+
+    let set: HashSet<_> = [1.2, 1.9, 2.0, -0.1, 2.99, f32::NAN, f32::NAN]
+        .into_iter()
+        .map(|val| CanonicalF32::new(val, Floor))
+        .collect();
+
+    assert!(set.len() == 4);
+    assert!(set.contains(&CanonicalF32::new(1.0, Floor)));
+    assert!(!set.contains(&CanonicalF32::new(3.0, Floor)));
+}
+
+#[test]
+fn it_buckets_between_breakpoints() {
+    use std::collections::HashSet;
+
+    let breakpoints = vec![-5.0, 0.0, 0.5, 20.0, 1000.0];
+    let bucket = |val| CanonicalF32::new(val, BreakpointCanonicalizer::new(&breakpoints));
+
+    assert!(bucket(0.1) == bucket(0.49));
+    assert!(bucket(0.0) == bucket(-0.0) && bucket(0.0) == bucket(0.3));
+    assert!(bucket(0.5) == bucket(19.9) && bucket(0.49) != bucket(0.5));
+    assert!(bucket(-100.0) == bucket(f32::NEG_INFINITY));
+    assert!(bucket(1000.0) == bucket(f32::INFINITY) && bucket(999.0) != bucket(1000.0));
+
+    let set: HashSet<_> = [-7.0, -6.0, -1.0, 0.2, 0.4, 3.0, 15.0, 5000.0]
+        .into_iter()
+        .map(bucket)
+        .collect();
+    assert!(set.len() == 5);
+    assert!(set.contains(&bucket(10.0)) && !set.contains(&bucket(500.0)));
+}
+
+#[test]
+fn it_never_equates_values_under_different_canonicalizers() {
+    use std::collections::hash_map::DefaultHasher;
+
+    let hash_of = |val: &CanonicalF32<GridCanonicalizer>| {
+        let mut hasher = DefaultHasher::new();
+        val.hash(&mut hasher);
+        hasher.finish()
+    };
+    let fine = GridCanonicalizer { tolerance: 0.1 };
+    let coarse = GridCanonicalizer { tolerance: 10.0 };
+    let values = [
+        CanonicalF32::new(1.0, fine),
+        CanonicalF32::new(1.0, coarse),
+        CanonicalF32::new(1.04, fine),
+        CanonicalF32::new(4.0, coarse),
+        CanonicalF32::new(f32::NAN, fine),
+        CanonicalF32::new(f32::NAN, coarse),
+    ];
+
+    assert!(values[0] != values[1] && values[4] != values[5]);
+    assert!(values[0] == values[2] && values[1] == values[3]);
+    for a in &values {
+        for b in &values {
+            assert!(a != b || hash_of(a) == hash_of(b), "{a:?} vs {b:?}");
+        }
+    }
+}
+
+#[test]
+fn it_matches_the_built_in_modes() {
+    use crate::ComparisonMode;
+
+    for val in [0.0, -3.5, 1e-30, 42.000004, f32::MAX] {
+        assert!(
+            GridCanonicalizer { tolerance: 1e-5 }.canonicalize(val)
+                == ComparisonMode::Absolute(1e-5).canonicalize(val)
+        );
+        assert!(
+            UlpCanonicalizer { ulps: 3 }.canonicalize(val)
+                == ComparisonMode::Ulps(3).canonicalize(val)
+        );
+    }
+}
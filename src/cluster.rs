@@ -0,0 +1,46 @@
+/// Groups `values` by single linkage: two values share a cluster if a chain
+/// of values each within `tolerance` of the next connects them, even when
+/// the ends of the chain are much further apart. Unlike the greedy
+/// first-representative clustering the sets do, the result doesn't depend on
+/// insertion order.
+///
+/// This is the partition a union-find over every within-tolerance pair would
+/// produce. In one dimension those pairs never skip over a value, so it's
+/// found by sorting and splitting wherever the gap to the next value is too
+/// wide. Clusters come back in ascending order, as do the values in each.
+/// Only identical NaNs are linked to each other.
+pub fn union_find_clusters(values: &[f32], tolerance: f32) -> Vec<Vec<f32>> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+
+    let mut clusters: Vec<Vec<f32>> = Vec::new();
+    for val in sorted {
+        match clusters.last_mut() {
+            Some(cluster) if linked(*cluster.last().unwrap(), val, tolerance) => cluster.push(val),
+            _ => clusters.push(vec![val]),
+        }
+    }
+    clusters
+}
+
+fn linked(a: f32, b: f32, tolerance: f32) -> bool {
+    a.to_bits() == b.to_bits() || (a - b).abs() <= tolerance
+}
+
+#[test]
+fn it_links_chains_into_one_cluster() {
+    // The ends are 0.000012 apart, but each is within tolerance of the middle
+    let clusters = union_find_clusters(&[1.000012, 1.0, 1.000006], 0.00001);
+
+    assert!(clusters == vec![vec![1.0, 1.000006, 1.000012]]);
+}
+
+#[test]
+fn it_splits_at_gaps_wider_than_tolerance() {
+    let clusters = union_find_clusters(&[3.0, 1.0, 1.5, 2.5, f32::NAN], 0.5);
+
+    assert!(clusters.len() == 3);
+    assert!(clusters[0] == vec![1.0, 1.5]);
+    assert!(clusters[1] == vec![2.5, 3.0]);
+    assert!(clusters[2][0].is_nan());
+}
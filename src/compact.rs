@@ -0,0 +1,81 @@
+#[cfg(test)]
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use crate::{F32Wrapper, F32_ERROR_TOLERANCE};
+
+/// An [`F32Wrapper`] squeezed into the 4 bytes of its value, for dense
+/// arrays of keys.
+///
+/// Instead of carrying a mode, every compact wrapper compares with the same
+/// absolute tolerance, [`F32_ERROR_TOLERANCE`]. Equality and hashing are
+/// exactly those of an `F32Wrapper` at that tolerance, so a set of either
+/// dedups the same values.
+#[derive(Debug, Copy, Clone)]
+pub struct CompactF32Wrapper(f32);
+
+impl CompactF32Wrapper {
+    pub fn new(val: f32) -> Self {
+        CompactF32Wrapper(val)
+    }
+
+    pub fn value(self) -> f32 {
+        self.0
+    }
+
+    fn expand(self) -> F32Wrapper {
+        F32Wrapper::with_tolerance(self.0, F32_ERROR_TOLERANCE)
+    }
+}
+
+impl PartialEq for CompactF32Wrapper {
+    fn eq(&self, other: &Self) -> bool {
+        self.expand() == other.expand()
+    }
+}
+
+impl Eq for CompactF32Wrapper {}
+
+impl Hash for CompactF32Wrapper {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.expand().hash(state);
+    }
+}
+
+/// Keeps only the value. The wrapper's own mode is dropped in favour of the
+/// shared tolerance.
+impl From<F32Wrapper> for CompactF32Wrapper {
+    fn from(val: F32Wrapper) -> Self {
+        CompactF32Wrapper(val.value())
+    }
+}
+
+/// A wrapper at the shared tolerance, whatever the thread's default is.
+impl From<CompactF32Wrapper> for F32Wrapper {
+    fn from(val: CompactF32Wrapper) -> Self {
+        val.expand()
+    }
+}
+
+#[test]
+fn it_fits_in_four_bytes() {
+    assert!(std::mem::size_of::<CompactF32Wrapper>() == 4);
+    assert!(std::mem::size_of::<[CompactF32Wrapper; 8]>() == 32);
+}
+
+#[test]
+fn it_dedups_like_a_full_wrapper() {
+    let values = [1.0, 1.000004, 2.0, -0.0, 0.0, 1.00002, f32::NAN, 2.000001];
+    let compact: HashSet<CompactF32Wrapper> =
+        values.iter().copied().map(CompactF32Wrapper::new).collect();
+    let full: HashSet<F32Wrapper> = values
+        .iter()
+        .map(|val| F32Wrapper::with_tolerance(*val, F32_ERROR_TOLERANCE))
+        .collect();
+
+    assert!(compact.len() == full.len());
+    assert!(compact
+        .iter()
+        .all(|val| full.contains(&F32Wrapper::from(*val))));
+    assert!(CompactF32Wrapper::from(F32Wrapper::new(3.5)).value() == 3.5);
+}
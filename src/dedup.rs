@@ -0,0 +1,66 @@
+use std::borrow::Cow;
+
+use crate::FloatHashSet;
+
+/// Drops values within `tolerance` of an earlier one, keeping the rest in
+/// order. Borrows `values` untouched if nothing had to go, so already-clean
+/// input costs no allocation.
+pub fn dedup_cow(values: &[f32], tolerance: f32) -> Cow<'_, [f32]> {
+    let mut seen = FloatHashSet::new(tolerance);
+    let mut kept: Option<Vec<f32>> = None;
+    for (idx, val) in values.iter().enumerate() {
+        let is_new = seen.insert(*val);
+        match (&mut kept, is_new) {
+            (Some(kept), true) => kept.push(*val),
+            (None, false) => kept = Some(values[..idx].to_vec()),
+            _ => {}
+        }
+    }
+    kept.map_or(Cow::Borrowed(values), Cow::Owned)
+}
+
+/// Keeps each value unless it's within `tolerance` of one already kept,
+/// preserving the original order. Only the first NaN is kept.
+///
+/// Every value is checked against every kept value, so this costs O(n·k) for
+/// `k` kept values: fine for short inputs, hopeless for long mostly-distinct
+/// ones. [`dedup_cow`] keeps exactly the same values but probes grid cells
+/// instead, in expected O(n).
+pub fn dedup_preserving_order(values: &[f32], tolerance: f32) -> Vec<f32> {
+    let mut kept: Vec<f32> = Vec::new();
+    for val in values {
+        let is_new = !kept.iter().any(|kept| {
+            kept.to_bits() == val.to_bits()
+                || (kept.is_nan() && val.is_nan())
+                || (kept - val).abs() <= tolerance
+        });
+        if is_new {
+            kept.push(*val);
+        }
+    }
+    kept
+}
+
+#[test]
+fn it_borrows_already_distinct_values() {
+    let values = [1.0, 2.0, 3.0];
+
+    assert!(matches!(dedup_cow(&values, 0.00001), Cow::Borrowed(_)));
+}
+
+#[test]
+fn it_owns_deduped_values() {
+    let values = [1.0, 2.0, 1.000004, 3.0, 2.0];
+
+    let deduped = dedup_cow(&values, 0.00001);
+    assert!(matches!(deduped, Cow::Owned(_)));
+    assert!(*deduped == [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn it_keeps_first_occurrences_in_order() {
+    let values = [3.0, 1.0, 3.000005, 2.0];
+
+    assert!(dedup_preserving_order(&values, 0.00001) == vec![3.0, 1.0, 2.0]);
+    assert!(*dedup_cow(&values, 0.00001) == [3.0, 1.0, 2.0]);
+}
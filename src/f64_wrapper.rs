@@ -0,0 +1,245 @@
+#[cfg(test)]
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::mode::validated_tolerance;
+#[cfg(test)]
+use crate::ComparisonMode;
+use crate::{grid, F32Wrapper, ToleranceError};
+
+pub const F64_ERROR_TOLERANCE: f64 = 0.00001;
+
+/// The `f64` counterpart of [`F32Wrapper`].
+///
+/// Equality works as for `F32Wrapper`: two wrappers are equal when they
+/// snap to the same grid cell, which is exactly when they hash alike.
+/// Wrappers at different tolerances, or with and without distinct zeros,
+/// are never equal, all NaNs are equal to each other, and with distinct
+/// zeros the sign has to match too.
+#[derive(Debug, Copy, Clone)]
+pub struct F64Wrapper {
+    inner: f64,
+    tolerance: f64,
+    distinct_zeros: bool,
+}
+
+impl PartialEq for F64Wrapper {
+    fn eq(&self, other: &Self) -> bool {
+        if self.tolerance != other.tolerance || self.distinct_zeros != other.distinct_zeros {
+            return false;
+        }
+        if self.inner.to_bits() == other.inner.to_bits() {
+            return true;
+        }
+        match (self.inner.is_nan(), other.inner.is_nan()) {
+            (true, true) => return true,
+            (true, false) | (false, true) => return false,
+            (false, false) => {}
+        }
+        if self.distinct_zeros && self.inner.is_sign_negative() != other.inner.is_sign_negative() {
+            return false;
+        }
+        grid::cell(self.inner, self.tolerance) == grid::cell(other.inner, self.tolerance)
+    }
+}
+
+impl Eq for F64Wrapper {}
+
+impl F64Wrapper {
+    pub fn new(val: f64) -> Self {
+        Self::with_tolerance(val, F64_ERROR_TOLERANCE)
+    }
+
+    /// `tolerance` must be finite and not negative, which debug builds
+    /// assert.
+    pub fn with_tolerance(val: f64, tolerance: f64) -> Self {
+        debug_assert!(
+            validated_tolerance(tolerance).is_ok(),
+            "invalid tolerance {tolerance}: must be finite and at least zero"
+        );
+        F64Wrapper {
+            inner: val,
+            tolerance,
+            distinct_zeros: false,
+        }
+    }
+
+    /// Like [`F64Wrapper::with_tolerance`], but returns an error for a
+    /// negative, NaN, or infinite tolerance in every build.
+    pub fn try_with_tolerance(val: f64, tolerance: f64) -> Result<Self, ToleranceError<f64>> {
+        Ok(Self::with_tolerance(val, validated_tolerance(tolerance)?))
+    }
+
+    pub fn value(self) -> f64 {
+        self.inner
+    }
+
+    pub fn tolerance(self) -> f64 {
+        self.tolerance
+    }
+}
+
+/// Widens both the value and its absolute tolerance, exactly. Every other
+/// mode is pinned to the absolute tolerance it works out to at the value
+/// (see [`F32Wrapper::tolerance`]), so the result no longer hashes or
+/// compares like the original, and a wrapper that ignores sign widens to
+/// its magnitude.
+impl From<F32Wrapper> for F64Wrapper {
+    fn from(val: F32Wrapper) -> Self {
+        F64Wrapper {
+            inner: f64::from(val.compared_value()),
+            tolerance: f64::from(val.tolerance()),
+            distinct_zeros: val.has_distinct_zeros(),
+        }
+    }
+}
+
+/// Hashes exactly like an absolute-mode [`F32Wrapper`] does. That already
+/// snaps to its grid in `f64`, after widening its value and tolerance, so a
+/// widened wrapper lands in the same cell and feeds the hasher the same
+/// bytes. Wrappers in other modes hash by their own cells, which a widened
+/// one can't reproduce. An
+/// `f64` that's merely close to an `f32` (like `0.1` vs `0.1f32`) agrees too,
+/// as long as the shared tolerance is much coarser than the rounding error
+/// between them and the value isn't sitting on a cell edge.
+impl Hash for F64Wrapper {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The same canonical bytes as `F32Wrapper::canonical_hash_into`
+        if self.inner.is_nan() {
+            state.write_u64(grid::mix(i64::MIN));
+            return;
+        }
+        let mixed = grid::mix(grid::cell(self.inner, self.tolerance));
+        if self.distinct_zeros && self.inner.is_sign_negative() {
+            state.write_u64(!mixed);
+        } else {
+            state.write_u64(mixed);
+        }
+    }
+}
+
+#[cfg(test)]
+fn hash_of<T: Hash>(val: T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    val.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn it_hashes_widened_wrappers_like_the_original() {
+    for val in [0.1, -3.75, 42.000004, 0.0, 1e-6] {
+        let narrow = F32Wrapper::new(val);
+        let wide = F64Wrapper::from(narrow);
+
+        assert!(hash_of(narrow) == hash_of(wide));
+    }
+}
+
+#[test]
+fn it_pins_other_modes_to_their_absolute_tolerance_when_widening() {
+    for narrow in [
+        F32Wrapper::with_relative(1234.5, 0.01),
+        F32Wrapper::with_mode(1234.5, ComparisonMode::Ulps(1000)),
+        F32Wrapper::with_magnitude_buckets(1234.5),
+        F32Wrapper::with_log_buckets(1234.5, 0.01),
+    ] {
+        let wide = F64Wrapper::from(narrow);
+        let pinned = F64Wrapper::with_tolerance(1234.5, f64::from(narrow.tolerance()));
+
+        assert!(wide == pinned && hash_of(wide) == hash_of(pinned));
+        assert!(hash_of(narrow) != hash_of(wide), "{narrow:?}");
+    }
+}
+
+#[test]
+fn it_hashes_integers_alike_in_both_precisions() {
+    for tolerance in [0.0, 0.00001, 0.5, 1.0, 3.0] {
+        for val in [5.0f32, 0.0, -7.0, 1024.0, 16_777_216.0] {
+            let narrow = F32Wrapper::with_tolerance(val, tolerance);
+            let wide = F64Wrapper::with_tolerance(f64::from(val), f64::from(tolerance));
+
+            assert!(hash_of(narrow) == hash_of(wide), "{val} at {tolerance}");
+        }
+    }
+    for narrow in [
+        F32Wrapper::new(f32::NAN),
+        F32Wrapper::with_distinct_zeros(-0.0),
+        F32Wrapper::with_distinct_zeros(-5.0),
+    ] {
+        assert!(hash_of(narrow) == hash_of(F64Wrapper::from(narrow)));
+    }
+}
+
+#[test]
+fn it_buckets_close_f64s_with_widened_f32s() {
+    let narrow = F32Wrapper::new(0.1);
+    let wide = F64Wrapper::with_tolerance(0.1, f64::from(narrow.tolerance()));
+
+    assert!(wide == F64Wrapper::from(narrow));
+    assert!(hash_of(narrow) == hash_of(wide));
+}
+
+#[test]
+fn it_hashes_the_largest_exponent_fields() {
+    // The exponent used to be rebuilt with `2i32.pow`, which can't hold an
+    // 11-bit exponent field. Hashing now goes through grid cells, so check
+    // nothing at the top of the range panics (overflow checks are on in
+    // tests) and the values there still hash apart
+    let hashes: Vec<u64> = [f64::MAX.next_down(), f64::MAX, f64::INFINITY, f64::NAN]
+        .into_iter()
+        .map(|val| hash_of(F64Wrapper::with_tolerance(val, 1e-300)))
+        .collect();
+
+    assert!(hashes[0] != hashes[1]);
+    assert!(hashes[1] != hashes[2]);
+    assert!(hash_of(F64Wrapper::new(f64::MAX)) != hash_of(F64Wrapper::new(f64::INFINITY)));
+}
+
+#[test]
+fn it_hashes_equal_wrappers_alike_even_across_cell_edges() {
+    let straddling = (
+        F64Wrapper::with_tolerance(0.000149, 0.0001),
+        F64Wrapper::with_tolerance(0.000151, 0.0001),
+    );
+    assert!(straddling.0 != straddling.1);
+
+    let narrow_zero = F64Wrapper::from(F32Wrapper::with_distinct_zeros(-0.0));
+    let wrappers = [
+        straddling.0,
+        straddling.1,
+        F64Wrapper::with_tolerance(0.00012, 0.0001),
+        F64Wrapper::with_tolerance(0.00012, 0.001),
+        F64Wrapper::with_tolerance(f64::NAN, 0.0001),
+        F64Wrapper::with_tolerance(-f64::NAN, 0.0001),
+        F64Wrapper::with_tolerance(0.0, 0.00001),
+        F64Wrapper::with_tolerance(-0.0, 0.00001),
+        narrow_zero,
+        F64Wrapper::from(F32Wrapper::with_distinct_zeros(0.0)),
+    ];
+    for a in &wrappers {
+        for b in &wrappers {
+            assert!(a != b || hash_of(a) == hash_of(b), "{a:?} vs {b:?}");
+        }
+    }
+    assert!(wrappers[0] == wrappers[2] && wrappers[2] != wrappers[3]);
+    assert!(wrappers[4] == wrappers[5] && wrappers[6] == wrappers[7]);
+    assert!(narrow_zero != wrappers[7] && narrow_zero != wrappers[9]);
+}
+
+#[test]
+fn it_rejects_negative_and_nan_tolerances() {
+    assert!(
+        F64Wrapper::try_with_tolerance(1.0, -0.1)
+            .unwrap_err()
+            .tolerance
+            == -0.1
+    );
+    assert!(F64Wrapper::try_with_tolerance(1.0, f64::NAN).is_err());
+    assert!(F64Wrapper::try_with_tolerance(1.0, f64::INFINITY).is_err());
+    assert!(
+        F64Wrapper::try_with_tolerance(1.0, 0.001)
+            .unwrap()
+            .tolerance()
+            == 0.001
+    );
+}
@@ -0,0 +1,44 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// The bits of IEEE 754 layout the tolerant containers need to know about a
+/// float type, so they can be written once for every precision.
+///
+/// `f16` would slot in here too, but it isn't a stable primitive yet.
+pub trait FloatBits: Copy + PartialOrd + Debug {
+    /// Unsigned integer type holding the raw bit pattern.
+    type Bits: Copy + Eq + Hash + Debug;
+
+    const BITS: usize;
+    const EXPONENT_BITS: usize;
+    const MANTISSA_BITS: usize;
+
+    fn to_bits(self) -> Self::Bits;
+
+    /// Widens (or passes through) the value, so arithmetic on tolerances can
+    /// happen in a single precision that's exact for every implementor.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_float_bits {
+    ($float:ty, $bits:ty, $exponent_bits:expr) => {
+        impl FloatBits for $float {
+            type Bits = $bits;
+
+            const BITS: usize = <$bits>::BITS as usize;
+            const EXPONENT_BITS: usize = $exponent_bits;
+            const MANTISSA_BITS: usize = <$float>::MANTISSA_DIGITS as usize - 1;
+
+            fn to_bits(self) -> Self::Bits {
+                <$float>::to_bits(self)
+            }
+
+            fn to_f64(self) -> f64 {
+                f64::from(self)
+            }
+        }
+    };
+}
+
+impl_float_bits!(f32, u32, 8);
+impl_float_bits!(f64, u64, 11);
@@ -0,0 +1,55 @@
+use core::hash::Hasher;
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a. Tiny, unkeyed, and depends on nothing but `core`, so the
+/// same input hashes the same on every platform, build, and Rust release,
+/// unlike `DefaultHasher`.
+pub(crate) struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(OFFSET_BASIS)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+
+    // The default goes through native-endian bytes
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+}
+
+#[test]
+fn it_matches_the_reference_vectors() {
+    let hash = |bytes: &[u8]| {
+        let mut hasher = FnvHasher::default();
+        hasher.write(bytes);
+        hasher.finish()
+    };
+
+    assert!(hash(b"") == 0xcbf2_9ce4_8422_2325);
+    assert!(hash(b"a") == 0xaf63_dc4c_8601_ec8c);
+    assert!(hash(b"foobar") == 0x8594_4171_f739_67e8);
+}
+
+#[test]
+fn it_hashes_u64s_as_little_endian_bytes_on_every_platform() {
+    let mut hasher = FnvHasher::default();
+    hasher.write_u64(0x0123_4567_89ab_cdef);
+
+    // Golden value; this must never change
+    assert!(hasher.finish() == 0x37eb_3f33_4776_1c55);
+}
@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+
+use crate::grid;
+
+/// An immutable [`FloatHashSet`](crate::FloatHashSet), from
+/// [`FloatHashSet::freeze`](crate::FloatHashSet::freeze).
+///
+/// The representatives live in one sorted `Vec`, so the set takes no more
+/// memory than the floats themselves, and a tolerant lookup is a binary
+/// search followed by a look at the neighbours either side. It answers
+/// membership queries the same as the set it was frozen from.
+///
+/// Frozen sets are themselves ordered, so whole collections can be deduped
+/// in a `BTreeSet<FrozenFloatSet>`. Sets compare by tolerance first, then
+/// lexicographically over their representatives, with NaN last. Each
+/// representative is compared by the grid cell it hashes into at that
+/// tolerance, not by its exact value, which keeps the order transitive. So
+/// sets built from slightly different values are equal as long as each pair
+/// of corresponding values shares a cell. A pair that straddles a cell edge
+/// makes the sets unequal, however close the values are.
+#[derive(Debug, Clone)]
+pub struct FrozenFloatSet {
+    tolerance: f32,
+    /// Every representative but NaN, in ascending order.
+    values: Vec<f32>,
+    has_nan: bool,
+}
+
+impl FrozenFloatSet {
+    pub(crate) fn from_representatives<I: IntoIterator<Item = f32>>(
+        representatives: I,
+        tolerance: f32,
+    ) -> Self {
+        let mut has_nan = false;
+        let mut values: Vec<f32> = representatives
+            .into_iter()
+            .filter(|val| {
+                has_nan |= val.is_nan();
+                !val.is_nan()
+            })
+            .collect();
+        values.sort_by(f32::total_cmp);
+        FrozenFloatSet {
+            tolerance,
+            values,
+            has_nan,
+        }
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Number of clusters.
+    pub fn len(&self) -> usize {
+        self.values.len() + usize::from(self.has_nan)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether a stored representative lies within tolerance of `value`. As
+    /// in the set, all NaNs count as one value.
+    pub fn contains(&self, value: f32) -> bool {
+        if value.is_nan() {
+            return self.has_nan;
+        }
+        let idx = self.values.partition_point(|stored| *stored < value);
+        let close = |stored: &f32| {
+            stored.to_bits() == value.to_bits()
+                || (f64::from(*stored) - f64::from(value)).abs() <= f64::from(self.tolerance)
+        };
+        self.values.get(idx).is_some_and(close)
+            || idx
+                .checked_sub(1)
+                .and_then(|below| self.values.get(below))
+                .is_some_and(close)
+    }
+
+    /// The representatives in ascending order, followed by NaN if there is
+    /// one.
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.values
+            .iter()
+            .copied()
+            .chain(self.has_nan.then_some(f32::NAN))
+    }
+
+    /// What the ordering compares: each representative's cell, then NaN
+    /// after all of them.
+    fn cells(&self) -> impl Iterator<Item = (bool, i64)> + '_ {
+        let tolerance = f64::from(self.tolerance);
+        self.values
+            .iter()
+            .map(move |val| (false, grid::cell(f64::from(*val), tolerance)))
+            .chain(self.has_nan.then_some((true, 0)))
+    }
+}
+
+impl PartialEq for FrozenFloatSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FrozenFloatSet {}
+
+impl PartialOrd for FrozenFloatSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FrozenFloatSet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.tolerance
+            .total_cmp(&other.tolerance)
+            .then_with(|| self.cells().cmp(other.cells()))
+    }
+}
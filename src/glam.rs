@@ -0,0 +1,64 @@
+//! Conversions between `glam` vectors and [`FloatArrayWrapper`], e.g. for
+//! welding mesh vertices.
+
+#[cfg(test)]
+use std::collections::HashSet;
+
+use glam::{Vec2, Vec3};
+
+use crate::FloatArrayWrapper;
+
+impl FloatArrayWrapper<2> {
+    pub fn from_vec2(val: Vec2, tolerance: f32) -> Self {
+        Self::with_tolerance(val.to_array(), tolerance)
+    }
+}
+
+impl FloatArrayWrapper<3> {
+    pub fn from_vec3(val: Vec3, tolerance: f32) -> Self {
+        Self::with_tolerance(val.to_array(), tolerance)
+    }
+}
+
+impl From<Vec2> for FloatArrayWrapper<2> {
+    fn from(val: Vec2) -> Self {
+        Self::new(val.to_array())
+    }
+}
+
+impl From<Vec3> for FloatArrayWrapper<3> {
+    fn from(val: Vec3) -> Self {
+        Self::new(val.to_array())
+    }
+}
+
+impl From<FloatArrayWrapper<2>> for Vec2 {
+    fn from(val: FloatArrayWrapper<2>) -> Self {
+        Vec2::from_array(val.value())
+    }
+}
+
+impl From<FloatArrayWrapper<3>> for Vec3 {
+    fn from(val: FloatArrayWrapper<3>) -> Self {
+        Vec3::from_array(val.value())
+    }
+}
+
+#[test]
+fn it_welds_near_coincident_vertices() {
+    let vertices = [
+        Vec3::new(0.5, 1.0, -2.0),
+        Vec3::new(0.50001, 0.99999, -2.00002),
+        Vec3::new(1.5, 1.0, -2.0),
+    ];
+
+    let welded: HashSet<_> = vertices
+        .iter()
+        .map(|vertex| FloatArrayWrapper::from_vec3(*vertex, 0.0001))
+        .collect();
+
+    assert!(welded.len() == 2);
+    assert!(welded
+        .iter()
+        .any(|vertex| Vec3::from(*vertex) == vertices[2]));
+}
@@ -0,0 +1,286 @@
+/// Index of the `tolerance`-wide grid cell centred nearest to `val`.
+///
+/// The math happens in `f64` so that `f32` inputs divide exactly enough for
+/// neighbouring cells to be told apart. `as` saturates, so infinities end up
+/// in the outermost cells and NaN in cell 0.
+///
+/// Every `f32`, subnormals included, is a normal `f64`, so nothing changes
+/// at `f32::MIN_POSITIVE`: cells stay evenly spaced across it, even at
+/// tolerances as small as the smallest subnormal, where each one holds
+/// exactly one value.
+///
+/// A zero tolerance would divide by zero, so instead every value gets a cell
+/// of its own (both zeros sharing one), derived from its bits.
+///
+/// Huge values would otherwise overflow the cell index, so once `|val|`
+/// passes `2^52 * tolerance` (where the quotient stops being able to land
+/// between integers anyway) the grid switches to one cell per representable
+/// `f64`, numbered on from the last regular cell. `f32::MAX` crosses over at
+/// any tolerance below roughly `7.6e22`. Only `f64`s at absurd ratios, like
+/// `f64::MAX` at a tolerance of `1e-300`, run out of cells and share the
+/// outermost one.
+///
+/// Ties go towards zero, so the zero cell spans all of `[-tolerance / 2,
+/// tolerance / 2]`. Values exactly half a tolerance either side of `0.0`
+/// thereby share its cell, rather than each losing it to a neighbour.
+#[inline]
+pub(crate) fn cell(val: f64, tolerance: f64) -> i64 {
+    cell_rounded(val, tolerance, Rounding::HalfTowardZero)
+}
+
+/// [`cell`] for an `f32` at an `f32` tolerance, with every NaN in
+/// `i64::MIN`. No number's cell reaches that far, so NaNs share a cell with
+/// each other and nothing else, as they do for [`crate::F32Wrapper`].
+#[inline]
+pub(crate) fn cell_or_nan(val: f32, tolerance: f32) -> i64 {
+    if val.is_nan() {
+        i64::MIN
+    } else {
+        cell(f64::from(val), f64::from(tolerance))
+    }
+}
+
+/// [`cell`], picking which cell a value between two centres goes to with
+/// `rounding`.
+#[allow(clippy::cast_possible_truncation)]
+#[inline]
+pub(crate) fn cell_rounded(val: f64, tolerance: f64, rounding: Rounding) -> i64 {
+    if tolerance == 0.0 {
+        return exact_cell(val);
+    }
+    let scaled = val / tolerance;
+    if scaled.abs() > MAX_REGULAR_CELL {
+        let past_crossover = exact_cell(val.abs()) - exact_cell(MAX_REGULAR_CELL * tolerance);
+        let cell = (MAX_REGULAR_CELL as i64).saturating_add(past_crossover);
+        return if val < 0.0 { -cell } else { cell };
+    }
+    rounding.apply(scaled) as i64
+}
+
+/// How a value is snapped to a grid point when it falls between two.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// To the nearest point, with ties going towards zero. This is what
+    /// hashing uses.
+    #[default]
+    HalfTowardZero,
+    /// To the nearest point, with ties going away from zero.
+    HalfUp,
+    /// To the nearest point, with ties going to the even one (banker's
+    /// rounding), so that ties don't bias sums upwards.
+    HalfEven,
+    /// Down to the point at or below.
+    Floor,
+    /// Up to the point at or above.
+    Ceil,
+}
+
+impl Rounding {
+    fn apply(self, val: f64) -> f64 {
+        match self {
+            Rounding::HalfTowardZero => {
+                let whole = val.trunc();
+                if (val - whole).abs() > 0.5 {
+                    whole + val.signum()
+                } else {
+                    whole
+                }
+            }
+            Rounding::HalfUp => val.round(),
+            Rounding::HalfEven => val.round_ties_even(),
+            Rounding::Floor => val.floor(),
+            Rounding::Ceil => val.ceil(),
+        }
+    }
+}
+
+/// Snaps `val` onto the grid of multiples of `tolerance`, the same grid the
+/// absolute mode hashes by.
+///
+/// Snapping is idempotent and never leaves `val`'s cell (as picked by
+/// `rounding`). So with the default [`Rounding::HalfTowardZero`], a wrapper of the
+/// snapped value always hashes like a wrapper of `val`.
+///
+/// Values are returned unchanged where the grid is no coarser than the floats
+/// themselves: at zero tolerance, at magnitudes past the per-ULP crossover
+/// described on the hashing grid, and for NaN and infinities.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+pub fn snap_to_grid(val: f32, tolerance: f32, rounding: Rounding) -> f32 {
+    let (val, tolerance) = (f64::from(val), f64::from(tolerance));
+    if tolerance == 0.0 || !val.is_finite() || (val / tolerance).abs() > MAX_REGULAR_CELL {
+        return val as f32;
+    }
+    let cell = cell_rounded(val, tolerance, rounding);
+    let snapped = (cell as f64 * tolerance) as f32;
+    // The nearest `f32` to the grid point is never further from it than
+    // `val` is, so this only trips on exact ties and rounding noise. Then
+    // `val` itself is the one float guaranteed to be in its own cell
+    if cell_rounded(f64::from(snapped), tolerance, rounding) != cell {
+        return val as f32;
+    }
+    snapped
+}
+
+/// Snaps every element of `values` onto the grid in place, as by
+/// [`snap_to_grid`] with the default rounding, so values sharing a cell
+/// become bit-identical and can be compared exactly afterwards.
+///
+/// Values within tolerance of each other but on either side of a cell edge
+/// still snap apart; use a [`crate::FloatHashSet`] when those must merge.
+pub fn normalize_in_place(values: &mut [f32], tolerance: f32) {
+    for val in values {
+        *val = snap_to_grid(*val, tolerance, Rounding::default());
+    }
+}
+
+/// The grid cell `val` hashes into at an absolute `tolerance`, along with
+/// its signed distance from that cell's centre. The residual lies within
+/// `±tolerance / 2`; one close to either end flags a value sitting near a
+/// cell edge, where a neighbour within tolerance may land in the next cell.
+///
+/// Where cells are single floats (zero tolerance, or magnitudes past the
+/// per-ULP crossover) the residual is 0. NaN is in cell 0 with a NaN
+/// residual.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+pub fn canonicalize(val: f32, tolerance: f32) -> (i64, f32) {
+    let (wide, tolerance) = (f64::from(val), f64::from(tolerance));
+    let cell = cell(wide, tolerance);
+    if val.is_nan() {
+        return (cell, f32::NAN);
+    }
+    if tolerance == 0.0 || (wide / tolerance).abs() > MAX_REGULAR_CELL {
+        return (cell, 0.0);
+    }
+    (cell, (wide - cell as f64 * tolerance) as f32)
+}
+
+/// `2^52`, past which every `f64` quotient is already a whole number.
+const MAX_REGULAR_CELL: f64 = 4_503_599_627_370_496.0;
+
+/// Orders the bits so that cells stay monotonic in `val`, with `-0.0` and
+/// `0.0` both at 0.
+#[allow(clippy::cast_possible_wrap)]
+fn exact_cell(val: f64) -> i64 {
+    let bits = val.to_bits();
+    let magnitude = (bits & !(1 << 63)) as i64;
+    if bits >> 63 == 1 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// MurmurHash3's 64-bit finalizer. It's a bijection, so distinct cells stay
+/// distinct, but flipping any input bit flips about half of the output bits.
+#[inline]
+pub(crate) fn mix(cell: i64) -> u64 {
+    #[allow(clippy::cast_sign_loss)]
+    let mut k = cell as u64;
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    k ^= k >> 33;
+    k
+}
+
+#[test]
+fn it_gives_every_value_its_own_cell_at_zero_tolerance() {
+    let one = 1.0f64;
+    let big = f64::from(f32::MAX);
+
+    assert!(cell(one, 0.0) != cell(one.next_up(), 0.0));
+    assert!(cell(one.next_down(), 0.0) < cell(one, 0.0));
+    assert!(cell(-one, 0.0) < cell(one, 0.0));
+    assert!(cell(big, 0.0) != cell(big.next_down(), 0.0));
+    assert!(cell(0.0, 0.0) == cell(-0.0, 0.0));
+}
+
+#[test]
+fn it_snaps_huge_values_without_overflowing() {
+    let tolerance = 0.00001;
+    let max = f64::from(f32::MAX);
+    let below_max = f64::from(f32::MAX.next_down());
+
+    assert!(cell(max, tolerance) > cell(below_max, tolerance));
+    assert!(cell(max, tolerance) < cell(f64::INFINITY, tolerance));
+    assert!(cell(-max, tolerance) == -cell(max, tolerance));
+}
+
+#[test]
+fn it_stays_monotonic_across_the_crossover() {
+    let tolerance = 0.5;
+    let crossover = MAX_REGULAR_CELL * tolerance;
+    let cells: Vec<i64> = [
+        crossover.next_down().next_down(),
+        crossover.next_down(),
+        crossover,
+        crossover.next_up(),
+        crossover.next_up().next_up(),
+    ]
+    .iter()
+    .map(|val| cell(*val, tolerance))
+    .collect();
+
+    assert!(cells
+        .windows(2)
+        .all(|pair| pair[1] - pair[0] <= 1 && pair[0] <= pair[1]));
+}
+
+#[test]
+fn it_snaps_ties_by_rounding_mode() {
+    let snap = |val, rounding| snap_to_grid(val, 1.0, rounding);
+
+    assert!(snap(2.5, Rounding::HalfTowardZero) == 2.0);
+    assert!(snap(2.51, Rounding::HalfTowardZero) == 3.0);
+    assert!(snap(-2.5, Rounding::HalfTowardZero) == -2.0);
+    assert!(snap(2.5, Rounding::HalfUp) == 3.0);
+    assert!(snap(2.5, Rounding::HalfEven) == 2.0);
+    assert!(snap(3.5, Rounding::HalfEven) == 4.0);
+    assert!(snap(2.5, Rounding::Floor) == 2.0);
+    assert!(snap(2.5, Rounding::Ceil) == 3.0);
+    assert!(snap(-2.5, Rounding::HalfUp) == -3.0);
+    assert!(snap(-2.5, Rounding::HalfEven) == -2.0);
+    assert!(snap(-2.5, Rounding::Floor) == -3.0);
+    assert!(snap(-2.5, Rounding::Ceil) == -2.0);
+}
+
+#[test]
+fn it_normalizes_near_equal_values_to_identical_bits() {
+    let mut values = [1.0, 1.000003, 0.999997, 2.5, 2.500004, -0.0, f32::NAN];
+    normalize_in_place(&mut values, 0.00001);
+
+    assert!(values[0].to_bits() == values[1].to_bits());
+    assert!(values[0].to_bits() == values[2].to_bits());
+    assert!(values[3].to_bits() == values[4].to_bits());
+    assert!(values[0] != values[3]);
+    assert!(values[5] == 0.0 && values[6].is_nan());
+}
+
+#[test]
+fn it_reports_residuals_from_the_cell_centre() {
+    let (cell, residual) = canonicalize(3.0, 1.0);
+    assert!(cell == 3 && residual == 0.0);
+
+    let (cell, residual) = canonicalize(3.49, 1.0);
+    assert!(cell == 3);
+    assert!((residual - 0.49).abs() < 1e-6);
+
+    let (cell, residual) = canonicalize(-3.49, 1.0);
+    assert!(cell == -3);
+    assert!((residual + 0.49).abs() < 1e-6);
+
+    assert!(canonicalize(f32::NAN, 1.0).1.is_nan());
+}
+
+#[test]
+fn it_keeps_half_a_tolerance_either_side_of_zero_in_the_zero_cell() {
+    let tolerance = 0.00001f32;
+    let half = f64::from(tolerance / 2.0);
+    let tolerance = f64::from(tolerance);
+
+    assert!(cell(half, tolerance) == 0);
+    assert!(cell(-half, tolerance) == 0);
+    assert!(cell(half.next_up(), tolerance) == 1);
+    assert!(cell(-half.next_up(), tolerance) == -1);
+}
@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use crate::grid::{self, Rounding};
+
+/// Counts `values` into bins `bin_width` wide, each centred on a multiple of
+/// `bin_width`, returning `(bin_center, count)` pairs in ascending order.
+/// Only occupied bins are listed.
+///
+/// Bins are the same grid cells an absolute tolerance of `bin_width` hashes
+/// by, so values that hash alike always share a bin. NaNs and infinities
+/// don't belong in any bin and are skipped.
+pub fn histogram(values: &[f32], bin_width: f32) -> Vec<(f32, usize)> {
+    let mut bins: BTreeMap<i64, (f32, usize)> = BTreeMap::new();
+    for val in values.iter().filter(|val| val.is_finite()) {
+        let cell = grid::cell(f64::from(*val), f64::from(bin_width));
+        bins.entry(cell)
+            .or_insert_with(|| (grid::snap_to_grid(*val, bin_width, Rounding::default()), 0))
+            .1 += 1;
+    }
+    bins.into_values().collect()
+}
+
+#[test]
+fn it_counts_values_per_bin() {
+    let values = [0.1, 0.4, 0.6, 1.2, 1.4, 1.45, -0.9, 3.0, f32::NAN];
+
+    let bins = histogram(&values, 1.0);
+
+    assert!(bins == vec![(-1.0, 1), (0.0, 2), (1.0, 4), (3.0, 1)]);
+}
@@ -0,0 +1,125 @@
+//! [`FloatIndexMap`], a tolerant map that remembers insertion order, on top
+//! of `indexmap`.
+
+use indexmap::IndexMap;
+
+use crate::F32Wrapper;
+
+/// A map from `f32` keys that keeps entries in insertion order, where keys
+/// within `tolerance` of a stored one count as that key.
+///
+/// Keys are stored as [`F32Wrapper`]s, one per grid cell. A wrapper lookup
+/// alone would miss a key within tolerance but over a cell edge, so lookups
+/// also probe the cells a tolerance either side and take the closest
+/// stored key within tolerance, the same as [`crate::ApproxHashMap`].
+/// Replacing a value keeps the entry's original position.
+#[derive(Debug, Clone)]
+pub struct FloatIndexMap<V> {
+    tolerance: f32,
+    entries: IndexMap<F32Wrapper, V>,
+}
+
+impl<V> FloatIndexMap<V> {
+    pub fn new(tolerance: f32) -> Self {
+        FloatIndexMap {
+            tolerance,
+            entries: IndexMap::new(),
+        }
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts `value` under `key` at the end of the order. If a stored key
+    /// is within tolerance its value is replaced in place and the old one
+    /// returned.
+    pub fn insert(&mut self, key: f32, value: V) -> Option<V> {
+        match self.find(key) {
+            Some(idx) => Some(std::mem::replace(&mut self.entries[idx], value)),
+            None => {
+                self.entries.insert(self.wrap(key), value);
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: f32) -> Option<&V> {
+        self.find(key).map(|idx| &self.entries[idx])
+    }
+
+    /// Where the entry within tolerance of `key` sits in insertion order.
+    pub fn get_index_of(&self, key: f32) -> Option<usize> {
+        self.find(key)
+    }
+
+    pub fn contains_key(&self, key: f32) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Every entry as its stored key and value, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (f32, &V)> + '_ {
+        self.entries.iter().map(|(key, value)| (key.value(), value))
+    }
+
+    fn wrap(&self, key: f32) -> F32Wrapper {
+        F32Wrapper::with_tolerance(key, self.tolerance)
+    }
+
+    /// The index of the stored key closest to `key` among those within
+    /// tolerance. Cells are `tolerance` wide, so the ones holding
+    /// `key - tolerance`, `key`, and `key + tolerance` cover every match.
+    fn find(&self, key: f32) -> Option<usize> {
+        let mut best: Option<(usize, f32)> = None;
+        for probe in [key - self.tolerance, key, key + self.tolerance] {
+            let Some((idx, stored, _)) = self.entries.get_full(&self.wrap(probe)) else {
+                continue;
+            };
+            let diff = if stored.value().to_bits() == key.to_bits()
+                || (stored.value().is_nan() && key.is_nan())
+            {
+                0.0
+            } else {
+                (stored.value() - key).abs()
+            };
+            if diff <= self.tolerance && best.is_none_or(|(_, best_diff)| diff < best_diff) {
+                best = Some((idx, diff));
+            }
+        }
+        best.map(|(idx, _)| idx)
+    }
+}
+
+#[test]
+fn it_looks_up_near_keys_in_insertion_order() {
+    let mut map = FloatIndexMap::new(0.01);
+    map.insert(3.0, "c");
+    map.insert(1.0, "a");
+    map.insert(2.0, "b");
+    assert!(map.insert(1.004, "a'") == Some("a"));
+
+    assert!(map.get(2.006) == Some(&"b"));
+    assert!(map.get(1.5).is_none());
+    assert!(map.get_index_of(0.995) == Some(1));
+    assert!(map.iter().collect::<Vec<_>>() == vec![(3.0, &"c"), (1.0, &"a'"), (2.0, &"b")]);
+}
+
+#[test]
+fn it_finds_keys_across_cell_edges() {
+    // 0.015 and 0.024 are within tolerance but in cells 1 and 2
+    let mut map = FloatIndexMap::new(0.01);
+    map.insert(0.015, 1);
+    map.insert(f32::NAN, 2);
+
+    assert!(map.get(0.024) == Some(&1));
+    assert!(map.get(-f32::NAN) == Some(&2));
+    assert!(map.len() == 2);
+}
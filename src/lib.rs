@@ -0,0 +1,72 @@
+//! Hash floats while treating values within an error tolerance as the *same*
+//! number. Please don't use this in any actual project.
+
+mod aggregate;
+mod approx;
+mod array;
+mod cache;
+mod cached;
+mod canonical;
+mod cluster;
+mod compact;
+mod dedup;
+mod f64_wrapper;
+mod float_bits;
+mod fnv;
+mod frozen;
+#[cfg(feature = "glam")]
+mod glam;
+mod grid;
+mod hasher;
+mod histogram;
+#[cfg(feature = "indexmap")]
+mod indexmap;
+#[macro_use]
+mod macros;
+pub mod map;
+mod mode;
+mod multiset;
+mod range_set;
+mod seconds;
+pub mod set;
+mod signal;
+mod small_set;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+mod weld;
+mod wrapper;
+
+#[cfg(feature = "indexmap")]
+pub use crate::indexmap::FloatIndexMap;
+pub use aggregate::{checked_sum, AggregateError, FloatStats};
+pub use approx::ApproxF32;
+pub use array::FloatArrayWrapper;
+pub use cache::FloatCache;
+pub use cached::CachedF32Wrapper;
+pub use canonical::{
+    BreakpointCanonicalizer, CanonicalF32, Canonicalizer, ExponentCanonicalizer, GridCanonicalizer,
+    LogCanonicalizer, RelativeCanonicalizer, UlpCanonicalizer,
+};
+pub use cluster::union_find_clusters;
+pub use compact::CompactF32Wrapper;
+pub use dedup::{dedup_cow, dedup_preserving_order};
+pub use f64_wrapper::{F64Wrapper, F64_ERROR_TOLERANCE};
+pub use float_bits::FloatBits;
+pub use frozen::FrozenFloatSet;
+pub use grid::{canonicalize, normalize_in_place, snap_to_grid, Rounding};
+pub use hasher::FixedState;
+pub use histogram::histogram;
+#[doc(hidden)]
+pub use macros::approx_set_mismatch as __approx_set_mismatch;
+pub use map::{ApproxHashMap, FloatHashMap, IntegrityError};
+pub use mode::{explain_eq, ulp_distance, ComparisonMode, EqExplanation, ToleranceError};
+pub use multiset::FloatMultiSet;
+pub use range_set::FloatRangeSet;
+pub use seconds::SecondsWrapper;
+pub use set::{FloatHashSet, RepresentativePolicy, TolerantDiff};
+pub use signal::SignalWrapper;
+pub use small_set::SmallFloatSet;
+pub use weld::weld_vertices;
+pub use wrapper::{
+    default_tolerance, set_default_tolerance, BitBreakdown, F32Wrapper, F32_ERROR_TOLERANCE,
+};
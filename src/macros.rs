@@ -0,0 +1,84 @@
+/// Asserts that a set's representatives match `expected`, in any order, with
+/// every expected value paired off against a distinct representative within
+/// `tolerance` of it. Works with anything that has an `iter()` yielding
+/// `f32`s, like [`FloatHashSet`](crate::FloatHashSet).
+///
+/// On failure the panic lists the expected values nothing matched and the
+/// representatives nothing expected.
+///
+/// ```
+/// use floaty_hash::{assert_approx_set_eq, FloatHashSet};
+///
+/// let mut set = FloatHashSet::new(0.00001);
+/// set.extend([2.0, 1.0, 1.000004]);
+/// assert_approx_set_eq!(set, [1.0, 2.0], 0.001);
+/// ```
+#[macro_export]
+macro_rules! assert_approx_set_eq {
+    ($set:expr, $expected:expr, $tolerance:expr $(,)?) => {{
+        let actual: ::std::vec::Vec<f32> = $set.iter().collect();
+        if let ::std::option::Option::Some(mismatch) =
+            $crate::__approx_set_mismatch(&actual, &$expected, $tolerance)
+        {
+            ::std::panic!("assertion failed: sets differ\n{}", mismatch);
+        }
+    }};
+}
+
+/// Pairs each expected value with the closest unclaimed actual one within
+/// `tolerance`, describing whatever is left over on either side.
+#[doc(hidden)]
+pub fn approx_set_mismatch(actual: &[f32], expected: &[f32], tolerance: f32) -> Option<String> {
+    let mut unmatched: Vec<f32> = actual.to_vec();
+    unmatched.sort_by(f32::total_cmp);
+    let mut expected = expected.to_vec();
+    expected.sort_by(f32::total_cmp);
+
+    let mut missing = Vec::new();
+    for want in expected {
+        let closest = unmatched
+            .iter()
+            .enumerate()
+            .filter(|(_, have)| {
+                have.to_bits() == want.to_bits() || (*have - want).abs() <= tolerance
+            })
+            .min_by(|(_, a), (_, b)| (*a - want).abs().total_cmp(&(*b - want).abs()))
+            .map(|(idx, _)| idx);
+        match closest {
+            Some(idx) => {
+                unmatched.remove(idx);
+            }
+            None => missing.push(want),
+        }
+    }
+
+    if missing.is_empty() && unmatched.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "  expected but missing: {missing:?}\n  present but unexpected: {unmatched:?}\n  (tolerance {tolerance})"
+    ))
+}
+
+#[test]
+fn it_accepts_matching_sets_in_any_order() {
+    let mut set = crate::FloatHashSet::new(0.00001);
+    set.extend([3.0, 1.0, 2.0, 1.000004]);
+
+    assert_approx_set_eq!(set, [1.0, 2.0, 3.0], 0.00001);
+    assert_approx_set_eq!(set, [2.000005, 0.999995, 3.0], 0.00001);
+}
+
+#[test]
+#[should_panic(expected = "expected but missing: [4.0]\n  present but unexpected: [3.0]")]
+fn it_reports_both_sides_of_a_mismatch() {
+    let mut set = crate::FloatHashSet::new(0.00001);
+    set.extend([1.0, 2.0, 3.0]);
+
+    assert_approx_set_eq!(set, [1.0, 2.0, 4.0], 0.00001);
+}
+
+#[test]
+fn it_pairs_each_representative_once() {
+    assert!(approx_set_mismatch(&[1.0], &[1.0, 1.000001], 0.00001).is_some());
+}
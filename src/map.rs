@@ -0,0 +1,671 @@
+use std::collections::hash_map::{self, RandomState};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::iter::Flatten;
+
+use crate::grid;
+use crate::mode::validated_tolerance;
+use crate::{FloatBits, ToleranceError};
+
+/// A map whose keys are considered the same when they're within `tolerance`
+/// of each other, for any float type implementing [`FloatBits`].
+///
+/// Unlike a `HashMap<F32Wrapper, V>`, lookups don't depend on two close keys
+/// happening to hash alike. Keys are filed into grid cells `tolerance` wide,
+/// and every lookup probes all of the cells that could hold a match, so a
+/// key within tolerance of a stored one is always found.
+///
+/// The first key inserted for a cluster stays its representative; later
+/// inserts within tolerance only replace the value.
+///
+/// NaNs never compare within tolerance of anything, so instead they're all
+/// treated as one key regardless of payload. Infinities only match
+/// themselves, as `inf - inf` is NaN.
+#[derive(Debug, Clone)]
+pub struct ApproxHashMap<F: FloatBits, V, S = RandomState> {
+    tolerance: F,
+    buckets: HashMap<i64, Vec<(F, V)>, S>,
+    len: usize,
+}
+
+/// The common case of an [`ApproxHashMap`] keyed by `f32`s.
+pub type FloatHashMap<V, S = RandomState> = ApproxHashMap<f32, V, S>;
+
+impl<F: FloatBits, V> ApproxHashMap<F, V, RandomState> {
+    /// Creates an empty map. `tolerance` is in the same units as the keys.
+    ///
+    /// It must be finite and not negative, which debug builds assert; see
+    /// [`ApproxHashMap::try_new`] for tolerances that aren't known to be.
+    pub fn new(tolerance: F) -> Self {
+        Self::with_hasher(tolerance, RandomState::new())
+    }
+
+    /// Like [`ApproxHashMap::new`], but returns an error for a negative,
+    /// NaN, or infinite tolerance in every build.
+    pub fn try_new(tolerance: F) -> Result<Self, ToleranceError<F>> {
+        Ok(Self::new(validated_tolerance(tolerance)?))
+    }
+}
+
+impl<F: FloatBits, V, S: BuildHasher> ApproxHashMap<F, V, S> {
+    /// Creates an empty map that hashes grid cells with `hash_builder`.
+    pub fn with_hasher(tolerance: F, hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(tolerance, 0, hash_builder)
+    }
+
+    /// Creates an empty map with room for at least `capacity` grid cells
+    /// before reallocating, hashing them with `hash_builder`.
+    pub fn with_capacity_and_hasher(tolerance: F, capacity: usize, hash_builder: S) -> Self {
+        debug_assert!(
+            validated_tolerance(tolerance).is_ok(),
+            "invalid tolerance {tolerance:?}: must be finite and at least zero"
+        );
+        ApproxHashMap {
+            tolerance,
+            buckets: HashMap::with_capacity_and_hasher(capacity, hash_builder),
+            len: 0,
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        self.buckets.hasher()
+    }
+
+    pub fn tolerance(&self) -> F {
+        self.tolerance
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts `value` under `key`. If a stored key is within tolerance its
+    /// value is replaced and the old one returned.
+    pub fn insert(&mut self, key: F, value: V) -> Option<V> {
+        if let Some((bucket, idx)) = self.find(key) {
+            let slot = &mut self.buckets.get_mut(&bucket).unwrap()[idx].1;
+            return Some(std::mem::replace(slot, value));
+        }
+        self.buckets
+            .entry(self.bucket_of(key.to_f64()))
+            .or_default()
+            .push((key, value));
+        self.len += 1;
+        None
+    }
+
+    /// Reserves room for at least `additional` more grid cells.
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.buckets.reserve(additional);
+    }
+
+    /// The entry for the cluster within tolerance of `key`, for in-place
+    /// updates in the style of [`HashMap::entry`]. If no cluster matches, the
+    /// entry is vacant and inserting into it starts one with `key` as the
+    /// representative.
+    pub fn entry(&mut self, key: F) -> Entry<'_, F, V, S> {
+        match self.find(key) {
+            Some((bucket, idx)) => Entry::Occupied(OccupiedEntry {
+                entry: &mut self.buckets.get_mut(&bucket).unwrap()[idx],
+            }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    pub fn get(&self, key: F) -> Option<&V> {
+        let (bucket, idx) = self.find(key)?;
+        Some(&self.buckets[&bucket][idx].1)
+    }
+
+    pub fn get_mut(&mut self, key: F) -> Option<&mut V> {
+        let (bucket, idx) = self.find(key)?;
+        Some(&mut self.buckets.get_mut(&bucket).unwrap()[idx].1)
+    }
+
+    /// Like [`ApproxHashMap::get`], but also returns the stored
+    /// representative key, which may differ from `key` by up to the tolerance.
+    pub fn get_key_value(&self, key: F) -> Option<(F, &V)> {
+        let (bucket, idx) = self.find(key)?;
+        let (stored, value) = &self.buckets[&bucket][idx];
+        Some((*stored, value))
+    }
+
+    pub fn contains_key(&self, key: F) -> bool {
+        self.find(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: F) -> Option<V> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes the entry within tolerance of `key`, returning its stored
+    /// representative key along with the value.
+    pub fn remove_entry(&mut self, key: F) -> Option<(F, V)> {
+        let (bucket, idx) = self.find(key)?;
+        let entries = self.buckets.get_mut(&bucket).unwrap();
+        let entry = entries.swap_remove(idx);
+        if entries.is_empty() {
+            self.buckets.remove(&bucket);
+        }
+        self.len -= 1;
+        Some(entry)
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.len = 0;
+    }
+
+    /// Releases spare capacity, e.g. after a bulk load followed by many
+    /// removals.
+    pub fn shrink_to_fit(&mut self) {
+        self.buckets.shrink_to_fit();
+        for entries in self.buckets.values_mut() {
+            entries.shrink_to_fit();
+        }
+    }
+
+    /// A rough estimate of the bytes allocated: the table's capacity times
+    /// the size of a slot plus every cell's entry capacity times the size of
+    /// an entry. It ignores the table's control bytes and any heap memory
+    /// the values themselves own.
+    pub fn memory_footprint(&self) -> usize {
+        let table = self.buckets.capacity() * std::mem::size_of::<(i64, Vec<(F, V)>)>();
+        let entries: usize = self
+            .buckets
+            .values()
+            .map(|entries| entries.capacity() * std::mem::size_of::<(F, V)>())
+            .sum();
+        table + entries
+    }
+
+    /// The stored representative keys, in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = F> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.iter_mut().map(|(_, value)| value)
+    }
+
+    /// Every entry as its representative key and value, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (F, &V)> + '_ {
+        self.buckets
+            .values()
+            .flat_map(|entries| entries.iter().map(|(key, value)| (*key, value)))
+    }
+
+    /// Like [`ApproxHashMap::iter`], with the values mutable. The keys
+    /// aren't, since moving one could take it out of its grid cell.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (F, &mut V)> + '_ {
+        self.buckets
+            .values_mut()
+            .flat_map(|entries| entries.iter_mut().map(|(key, value)| (*key, value)))
+    }
+
+    /// Keeps only the entries `keep` returns `true` for.
+    pub fn retain<K: FnMut(F, &mut V) -> bool>(&mut self, mut keep: K) {
+        let mut len = 0;
+        self.buckets.retain(|_, entries| {
+            entries.retain_mut(|(key, value)| keep(*key, value));
+            len += entries.len();
+            !entries.is_empty()
+        });
+        self.len = len;
+    }
+
+    /// Passes every value through `f`, keeping the keys, their grid cells,
+    /// and the hasher as they are.
+    pub fn map_values<W, M: FnMut(V) -> W>(self, mut f: M) -> ApproxHashMap<F, W, S>
+    where
+        S: Clone,
+    {
+        let mut buckets =
+            HashMap::with_capacity_and_hasher(self.buckets.len(), self.buckets.hasher().clone());
+        buckets.extend(self.buckets.into_iter().map(|(bucket, entries)| {
+            let entries = entries
+                .into_iter()
+                .map(|(key, value)| (key, f(value)))
+                .collect();
+            (bucket, entries)
+        }));
+        ApproxHashMap {
+            tolerance: self.tolerance,
+            buckets,
+            len: self.len,
+        }
+    }
+
+    /// Checks the invariants every lookup relies on: that each key is filed
+    /// under its own grid cell, and that no two keys are within tolerance of
+    /// each other (which `insert` merges, so finding a pair means a bucketing
+    /// bug). Reports the first violation found. Every key's neighbouring
+    /// cells get probed, so this costs about as much as looking up each key.
+    pub fn validate(&self) -> Result<(), IntegrityError<F>> {
+        let tolerance = self.tolerance.to_f64();
+        for (&bucket, entries) in &self.buckets {
+            for (idx, (key, _)) in entries.iter().enumerate() {
+                let val = key.to_f64();
+                if self.bucket_of(val) != bucket {
+                    return Err(IntegrityError::Misfiled { key: *key, bucket });
+                }
+                for other_bucket in
+                    self.bucket_of(val - tolerance)..=self.bucket_of(val + tolerance)
+                {
+                    let Some(others) = self.buckets.get(&other_bucket) else {
+                        continue;
+                    };
+                    for (other_idx, (other, _)) in others.iter().enumerate() {
+                        if (other_bucket, other_idx) == (bucket, idx) {
+                            continue;
+                        }
+                        let other_val = other.to_f64();
+                        let clash = (val.is_nan() && other_val.is_nan())
+                            || other.to_bits() == key.to_bits()
+                            || (other_val - val).abs() <= tolerance;
+                        if clash {
+                            return Err(IntegrityError::WithinTolerance(*key, *other));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Files `key` into its cell without checking for a match, to build
+    /// broken maps for [`ApproxHashMap::validate`] to catch.
+    #[cfg(test)]
+    pub(crate) fn insert_unchecked(&mut self, key: F, value: V) {
+        self.buckets
+            .entry(self.bucket_of(key.to_f64()))
+            .or_default()
+            .push((key, value));
+        self.len += 1;
+    }
+
+    pub(crate) fn into_entries(self) -> IntoEntries<F, V> {
+        IntoEntries {
+            inner: self.buckets.into_values().flatten(),
+        }
+    }
+
+    /// The stored key numerically closest to `key`, however far away. NaN
+    /// only finds a stored NaN, and stored NaNs are never nearest to
+    /// anything else.
+    ///
+    /// Rings of cells are searched outward from `key`'s own, which is quick
+    /// when something is nearby. Once the rings have covered as many cells
+    /// as the map holds (or if the grid has no regular-width cells to ring,
+    /// at zero tolerance or an infinite `key`), every key is scanned instead.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+    pub(crate) fn nearest_key(&self, key: F) -> Option<F> {
+        let val = key.to_f64();
+        if val.is_nan() {
+            return self
+                .find(key)
+                .map(|(bucket, idx)| self.buckets[&bucket][idx].0);
+        }
+        let closer = |best: Option<(F, f64)>, stored: F| {
+            let diff = if stored.to_bits() == key.to_bits() {
+                0.0
+            } else {
+                (stored.to_f64() - val).abs()
+            };
+            match best {
+                _ if diff.is_nan() => best,
+                Some((_, best_diff)) if best_diff <= diff => best,
+                _ => Some((stored, diff)),
+            }
+        };
+
+        let tolerance = self.tolerance.to_f64();
+        if tolerance > 0.0 && val.is_finite() {
+            let centre = self.bucket_of(val);
+            let mut best = None;
+            for ring in 0..=self.buckets.len() as i64 {
+                // A cell `ring` away is never closer than this to `val`
+                if best.is_some_and(|(_, diff)| (ring - 1) as f64 * tolerance > diff) {
+                    return best.map(|(stored, _)| stored);
+                }
+                for bucket in [centre.saturating_sub(ring), centre.saturating_add(ring)] {
+                    if let Some(entries) = self.buckets.get(&bucket) {
+                        best = entries.iter().map(|(stored, _)| *stored).fold(best, closer);
+                    }
+                    if ring == 0 {
+                        break;
+                    }
+                }
+            }
+        }
+        self.keys().fold(None, closer).map(|(stored, _)| stored)
+    }
+
+    fn bucket_of(&self, val: f64) -> i64 {
+        // NaN keys all land in cell 0 (at zero tolerance the grid would
+        // otherwise sort them by payload), where `find` lumps them together
+        if val.is_nan() {
+            return 0;
+        }
+        grid::cell(val, self.tolerance.to_f64())
+    }
+
+    /// Locates the stored key closest to `key` among those within tolerance.
+    fn find(&self, key: F) -> Option<(i64, usize)> {
+        let val = key.to_f64();
+        let tolerance = self.tolerance.to_f64();
+        let lo = self.bucket_of(val - tolerance);
+        let hi = self.bucket_of(val + tolerance);
+
+        let mut best: Option<(i64, usize, f64)> = None;
+        for bucket in lo..=hi {
+            let Some(entries) = self.buckets.get(&bucket) else {
+                continue;
+            };
+            for (idx, (stored, _)) in entries.iter().enumerate() {
+                let diff = if stored.to_bits() == key.to_bits()
+                    || (stored.to_f64().is_nan() && val.is_nan())
+                {
+                    0.0
+                } else {
+                    (stored.to_f64() - val).abs()
+                };
+                if diff <= tolerance && best.is_none_or(|(_, _, best_diff)| diff < best_diff) {
+                    best = Some((bucket, idx, diff));
+                }
+            }
+        }
+        best.map(|(bucket, idx, _)| (bucket, idx))
+    }
+}
+
+/// A broken invariant found by [`ApproxHashMap::validate`] or
+/// [`crate::FloatHashSet::validate`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IntegrityError<F = f32> {
+    /// `key` is stored under `bucket`, which isn't the grid cell it falls
+    /// in, so lookups for it may miss.
+    Misfiled { key: F, bucket: i64 },
+    /// Two stored keys are within tolerance of each other, so should have
+    /// been merged into one.
+    WithinTolerance(F, F),
+}
+
+impl<F: FloatBits> fmt::Display for IntegrityError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::Misfiled { key, bucket } => {
+                write!(
+                    f,
+                    "key {} is filed under the wrong cell {bucket}",
+                    key.to_f64()
+                )
+            }
+            IntegrityError::WithinTolerance(a, b) => write!(
+                f,
+                "keys {} and {} are within tolerance but stored apart",
+                a.to_f64(),
+                b.to_f64()
+            ),
+        }
+    }
+}
+
+impl<F: FloatBits> Error for IntegrityError<F> {}
+
+/// A view into a single cluster of an [`ApproxHashMap`], from
+/// [`ApproxHashMap::entry`].
+pub enum Entry<'a, F: FloatBits, V, S> {
+    Occupied(OccupiedEntry<'a, F, V>),
+    Vacant(VacantEntry<'a, F, V, S>),
+}
+
+impl<'a, F: FloatBits, V, S: BuildHasher> Entry<'a, F, V, S> {
+    /// The stored representative if occupied, otherwise the key looked up.
+    pub fn key(&self) -> F {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<D: FnOnce() -> V>(self, default: D) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<M: FnOnce(&mut V)>(mut self, modify: M) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            modify(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// An entry for a cluster that's already in the map.
+pub struct OccupiedEntry<'a, F, V> {
+    entry: &'a mut (F, V),
+}
+
+impl<'a, F: FloatBits, V> OccupiedEntry<'a, F, V> {
+    /// The cluster's representative, which may differ from the key looked up
+    /// by up to the tolerance.
+    pub fn key(&self) -> F {
+        self.entry.0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.entry.1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.entry.1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.entry.1
+    }
+
+    /// Replaces the value, returning the old one. The representative stays.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(&mut self.entry.1, value)
+    }
+}
+
+/// An entry for a key that isn't within tolerance of any cluster yet.
+pub struct VacantEntry<'a, F: FloatBits, V, S> {
+    map: &'a mut ApproxHashMap<F, V, S>,
+    key: F,
+}
+
+impl<'a, F: FloatBits, V, S: BuildHasher> VacantEntry<'a, F, V, S> {
+    pub fn key(&self) -> F {
+        self.key
+    }
+
+    /// Starts a new cluster with the key as its representative.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let bucket = self.map.bucket_of(self.key.to_f64());
+        let entries = self.map.buckets.entry(bucket).or_default();
+        entries.push((self.key, value));
+        self.map.len += 1;
+        &mut entries.last_mut().unwrap().1
+    }
+}
+
+pub(crate) struct IntoEntries<F, V> {
+    inner: Flatten<hash_map::IntoValues<i64, Vec<(F, V)>>>,
+}
+
+impl<F, V> Iterator for IntoEntries<F, V> {
+    type Item = (F, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+#[test]
+fn it_looks_up_close_f32_keys() {
+    let mut map = ApproxHashMap::new(0.00001f32);
+    map.insert(42.0, "answer");
+
+    assert!(map.get(42.0 + 0.000005) == Some(&"answer"));
+    assert!(map.get(42.0 - 0.000005) == Some(&"answer"));
+    assert!(map.get(42.0 + 0.00002).is_none());
+}
+
+#[test]
+fn it_rejects_negative_and_nan_tolerances() {
+    assert!(FloatHashMap::<()>::try_new(-0.1).unwrap_err().tolerance == -0.1);
+    assert!(FloatHashMap::<()>::try_new(f32::NAN).is_err());
+    assert!(ApproxHashMap::<f64, ()>::try_new(f64::INFINITY).is_err());
+    assert!(ApproxHashMap::<f64, ()>::try_new(0.0).is_ok());
+    assert!(FloatHashMap::<()>::try_new(0.001).unwrap().tolerance() == 0.001);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "invalid tolerance")]
+fn it_asserts_on_a_negative_tolerance_in_debug() {
+    FloatHashMap::<()>::new(-0.1);
+}
+
+#[test]
+fn it_catches_misfiled_keys() {
+    let mut map = ApproxHashMap::new(1.0f32);
+    map.insert(3.0, ());
+    map.insert(7.0, ());
+    assert!(map.validate().is_ok());
+
+    let entries = map.buckets.remove(&7).unwrap();
+    map.buckets.insert(5, entries);
+
+    assert!(
+        map.validate()
+            == Err(IntegrityError::Misfiled {
+                key: 7.0,
+                bucket: 5
+            })
+    );
+}
+
+#[test]
+fn it_looks_up_close_f64_keys() {
+    let mut map = ApproxHashMap::new(1e-9f64);
+    map.insert(1.0, 1);
+    map.insert(1.0 + 5e-10, 2);
+    map.insert(2.0, 3);
+
+    assert!(map.len() == 2);
+    assert!(map.get(1.0 - 5e-10) == Some(&2));
+    assert!(map.remove(2.0 + 1e-10) == Some(3));
+    assert!(map.len() == 1);
+}
+
+#[test]
+fn it_finds_keys_across_cell_boundaries() {
+    // 0.5 * tolerance sits exactly on the edge between cells 0 and 1
+    let mut map = ApproxHashMap::new(1.0f32);
+    map.insert(0.4, ());
+
+    assert!(map.contains_key(0.6));
+    assert!(map.contains_key(1.4));
+    assert!(!map.contains_key(1.5));
+}
+
+#[test]
+fn it_treats_every_nan_as_one_key() {
+    let quiet = f32::NAN;
+    let payload = f32::from_bits(f32::NAN.to_bits() | 1);
+    for tolerance in [0.0, 0.5] {
+        let mut map = ApproxHashMap::new(tolerance);
+        map.insert(quiet, 1);
+        map.insert(-payload, 2);
+
+        assert!(map.len() == 1);
+        assert!(map.get(payload) == Some(&2));
+        assert!(!map.contains_key(0.0));
+    }
+}
+
+#[test]
+fn it_accumulates_through_entries() {
+    let tolerance = 0.00001;
+    let mut map = FloatHashMap::new(tolerance);
+    *map.entry(1.0).or_insert(0) += 1;
+    *map.entry(1.0 + tolerance / 4.0).or_insert(0) += 1;
+    map.entry(1.0 - tolerance / 4.0)
+        .and_modify(|count| *count += 10);
+    map.entry(5.0).and_modify(|count| *count += 10);
+
+    assert!(map.len() == 1);
+    assert!(map.get(1.0 + tolerance / 2.0) == Some(&12));
+    assert!(map.entry(1.0 + tolerance / 2.0).key() == 1.0);
+    assert!(!map.contains_key(5.0));
+}
+
+#[test]
+fn it_maps_values_keeping_the_keys() {
+    let mut map = FloatHashMap::new(0.001);
+    for i in -5..5i16 {
+        map.insert(f32::from(i) * 0.5, i32::from(i));
+    }
+
+    let squares = map.map_values(|val| val * val);
+
+    assert!(squares.len() == 10);
+    assert!(squares.get(-2.5004) == Some(&25));
+    assert!(squares.get(1.5) == Some(&9));
+    assert!(squares.get_key_value(0.0009).map(|(key, _)| key) == Some(0.0));
+    assert!(squares.get(2.75).is_none());
+}
+
+#[test]
+fn it_iterates_representatives_and_values() {
+    let mut map = FloatHashMap::new(0.01);
+    map.insert(1.0, 10);
+    map.insert(1.004, 11);
+    map.insert(2.0, 20);
+    map.insert(-3.0, 30);
+
+    let mut keys: Vec<f32> = map.keys().collect();
+    keys.sort_by(f32::total_cmp);
+    assert!(keys == vec![-3.0, 1.0, 2.0]);
+    let mut values: Vec<i32> = map.values().copied().collect();
+    values.sort_unstable();
+    assert!(values == vec![11, 20, 30]);
+
+    map.values_mut().for_each(|val| *val += 1);
+    for (key, val) in map.iter_mut() {
+        if key < 0.0 {
+            *val = 0;
+        }
+    }
+    let mut entries: Vec<(f32, i32)> = map.iter().map(|(key, val)| (key, *val)).collect();
+    entries.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    assert!(entries == vec![(-3.0, 0), (1.0, 12), (2.0, 21)]);
+}
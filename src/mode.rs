@@ -0,0 +1,405 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::canonical::{
+    Canonicalizer, ExponentCanonicalizer, GridCanonicalizer, LogCanonicalizer,
+    RelativeCanonicalizer, UlpCanonicalizer,
+};
+use crate::FloatBits;
+
+/// The criterion used to decide whether two floats are "the same".
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ComparisonMode {
+    /// Equal if `|a - b| <= tolerance`.
+    Absolute(f32),
+    /// Equal if `|a - b| <= tolerance * max(|a|, |b|)`. Near zero the scale
+    /// bottoms out at `f32::MIN_POSITIVE`, since otherwise nothing but zero
+    /// itself would be relatively close to zero.
+    Relative(f32),
+    /// Equal if at most this many representable floats lie between them.
+    Ulps(u32),
+    /// Equal if the sign and exponent bits match, ignoring the mantissa, so
+    /// that e.g. all of `[1.0, 2.0)` is one order-of-magnitude bucket. Unlike
+    /// the others this is a true equivalence relation. The sign counts even
+    /// for zero, subnormals share the bucket of their sign's zero, and each
+    /// infinity shares one with the NaNs of its sign.
+    Magnitude,
+    /// Equal if the magnitudes fall in the same bucket `[(1 + ratio)^k,
+    /// (1 + ratio)^(k + 1))`, e.g. the same 1% band for a ratio of `0.01`,
+    /// which suits data spanning many decades. Like [`Self::Magnitude`] (the
+    /// buckets of a ratio of `1.0`) this is a true equivalence relation, so
+    /// values just either side of a bucket edge are unequal however close.
+    /// Zeros are in a bucket of their own, and negatives mirror positives.
+    LogBuckets(f32),
+}
+
+impl ComparisonMode {
+    /// The mode itself if its tolerance is usable, i.e. finite and at least
+    /// zero. A negative tolerance matches nothing and a NaN one hashes every
+    /// value into cell 0, so neither is ever what was meant.
+    pub fn validated(self) -> Result<Self, ToleranceError> {
+        match self {
+            ComparisonMode::Absolute(tolerance)
+            | ComparisonMode::Relative(tolerance)
+            | ComparisonMode::LogBuckets(tolerance)
+                if !(tolerance.is_finite() && tolerance >= 0.0) =>
+            {
+                Err(ToleranceError { tolerance })
+            }
+            _ => Ok(self),
+        }
+    }
+
+    #[inline]
+    pub fn matches(self, a: f32, b: f32) -> bool {
+        if a.to_bits() == b.to_bits() {
+            return true;
+        }
+        let diff = (a - b).abs();
+        match self {
+            ComparisonMode::Absolute(tolerance) => diff <= tolerance,
+            ComparisonMode::Relative(tolerance) => diff <= tolerance * relative_scale(a, b),
+            ComparisonMode::Ulps(ulps) => ulp_distance(a, b) <= u64::from(ulps),
+            ComparisonMode::Magnitude => sign_and_exponent(a) == sign_and_exponent(b),
+            ComparisonMode::LogBuckets(_) => self.cell(a) == self.cell(b),
+        }
+    }
+
+    /// The same mode with its tolerance parameter multiplied by `factor`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub(crate) fn scaled(self, factor: f32) -> Self {
+        match self {
+            ComparisonMode::Absolute(tolerance) => ComparisonMode::Absolute(tolerance * factor),
+            ComparisonMode::Relative(tolerance) => ComparisonMode::Relative(tolerance * factor),
+            ComparisonMode::Ulps(ulps) => {
+                ComparisonMode::Ulps((ulps as f32 * factor).round() as u32)
+            }
+            ComparisonMode::Magnitude => ComparisonMode::Magnitude,
+            ComparisonMode::LogBuckets(ratio) => ComparisonMode::LogBuckets(ratio * factor),
+        }
+    }
+
+    /// The absolute tolerance this mode works out to around `val`.
+    pub(crate) fn tolerance_at(self, val: f32) -> f32 {
+        match self {
+            ComparisonMode::Absolute(tolerance) => tolerance,
+            ComparisonMode::Relative(tolerance) => tolerance * val.abs().max(f32::MIN_POSITIVE),
+            #[allow(clippy::cast_precision_loss)]
+            ComparisonMode::Ulps(ulps) => (val.abs().next_up() - val.abs()) * ulps as f32,
+            ComparisonMode::Magnitude => {
+                // The width of the bucket: from the power of two at or below
+                // `|val|` to the next one up
+                f32::from_bits(val.abs().to_bits() & 0x7f80_0000).max(f32::MIN_POSITIVE)
+            }
+            ComparisonMode::LogBuckets(_) => {
+                let (lo, hi) = self.band(val.abs());
+                hi - lo
+            }
+        }
+    }
+
+    /// The smallest and largest floats that match `val` under this mode,
+    /// computed in `f32`, so the ends may be off by an ULP or so.
+    pub(crate) fn band(self, val: f32) -> (f32, f32) {
+        if val.is_nan() {
+            return (val, val);
+        }
+        match self {
+            ComparisonMode::Absolute(tolerance) => (val - tolerance, val + tolerance),
+            ComparisonMode::Relative(tolerance) => {
+                // Worked out for the magnitude, then mirrored for negatives
+                let magnitude = val.abs();
+                let floor = tolerance * f32::MIN_POSITIVE;
+                let hi = if tolerance < 1.0 {
+                    (magnitude / (1.0 - tolerance)).max(magnitude + floor)
+                } else {
+                    f32::INFINITY
+                };
+                let lo = (magnitude * (1.0 - tolerance)).min(magnitude - floor);
+                if val.is_sign_negative() {
+                    (-hi, -lo)
+                } else {
+                    (lo, hi)
+                }
+            }
+            ComparisonMode::Ulps(ulps) => {
+                let ordered = ordered_bits(val);
+                (
+                    from_ordered_bits(ordered - i64::from(ulps)),
+                    from_ordered_bits(ordered + i64::from(ulps)),
+                )
+            }
+            ComparisonMode::Magnitude => {
+                let bucket = val.to_bits() & 0xff80_0000;
+                if bucket & 0x7f80_0000 == 0x7f80_0000 {
+                    // The infinity's bucket; the rest of it is NaN
+                    return (val, val);
+                }
+                let (first, last) = (f32::from_bits(bucket), f32::from_bits(bucket | 0x007f_ffff));
+                if val.is_sign_negative() {
+                    (last, first)
+                } else {
+                    (first, last)
+                }
+            }
+            ComparisonMode::LogBuckets(ratio) => {
+                let magnitude = val.abs();
+                if ratio == 0.0 || magnitude == 0.0 || magnitude.is_infinite() {
+                    return (val, val);
+                }
+                #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+                let edge = |k: i64| (f64::from(ratio).ln_1p() * k as f64).exp() as f32;
+                let k = LogCanonicalizer { ratio }.bucket(magnitude);
+                let first = edge(k).min(magnitude);
+                let last = edge(k + 1).next_down().max(magnitude);
+                if val.is_sign_negative() {
+                    (-last, -first)
+                } else {
+                    (first, last)
+                }
+            }
+        }
+    }
+
+    /// The grid cell `val` snaps to when hashed under this mode, from the
+    /// matching [`Canonicalizer`].
+    #[inline]
+    pub(crate) fn cell(self, val: f32) -> i64 {
+        match self {
+            ComparisonMode::Absolute(tolerance) => {
+                GridCanonicalizer { tolerance }.canonicalize(val)
+            }
+            ComparisonMode::Relative(tolerance) => {
+                RelativeCanonicalizer { tolerance }.canonicalize(val)
+            }
+            ComparisonMode::Ulps(ulps) => UlpCanonicalizer { ulps }.canonicalize(val),
+            ComparisonMode::Magnitude => ExponentCanonicalizer.canonicalize(val),
+            ComparisonMode::LogBuckets(ratio) => LogCanonicalizer { ratio }.canonicalize(val),
+        }
+    }
+}
+
+/// Each mode hashes through the canonicalizer of the same name.
+impl Canonicalizer for ComparisonMode {
+    fn canonicalize(&self, val: f32) -> i64 {
+        self.cell(val)
+    }
+}
+
+/// A tolerance that was negative, NaN, or infinite.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ToleranceError<F = f32> {
+    pub tolerance: F,
+}
+
+impl<F: fmt::Display> fmt::Display for ToleranceError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tolerance must be finite and at least zero, got {}",
+            self.tolerance
+        )
+    }
+}
+
+impl<F: fmt::Debug + fmt::Display> Error for ToleranceError<F> {}
+
+/// `tolerance` itself if it's finite and at least zero, for the containers
+/// and wrappers that take a bare tolerance rather than a [`ComparisonMode`].
+pub(crate) fn validated_tolerance<F: FloatBits>(tolerance: F) -> Result<F, ToleranceError<F>> {
+    let widened = tolerance.to_f64();
+    if widened.is_finite() && widened >= 0.0 {
+        Ok(tolerance)
+    } else {
+        Err(ToleranceError { tolerance })
+    }
+}
+
+fn relative_scale(a: f32, b: f32) -> f32 {
+    a.abs().max(b.abs()).max(f32::MIN_POSITIVE)
+}
+
+/// The sign bit and exponent field, with the mantissa shifted away.
+pub(crate) fn sign_and_exponent(val: f32) -> u32 {
+    val.to_bits() >> 23
+}
+
+/// Maps bits onto a number line that's monotonic across zero, with both
+/// zeros at 0, so that subtracting gives a distance in ULPs.
+pub(crate) fn ordered_bits(val: f32) -> i64 {
+    let bits = val.to_bits();
+    let magnitude = i64::from(bits & 0x7fff_ffff);
+    if bits >> 31 == 1 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// The inverse of [`ordered_bits`], saturating at the infinities.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn from_ordered_bits(ordered: i64) -> f32 {
+    let magnitude = ordered
+        .unsigned_abs()
+        .min(u64::from(f32::INFINITY.to_bits())) as u32;
+    if ordered < 0 {
+        f32::from_bits(magnitude | 1 << 31)
+    } else {
+        f32::from_bits(magnitude)
+    }
+}
+
+/// Number of representable floats between `a` and `b`, or `u64::MAX` if
+/// either is NaN.
+///
+/// The bits are mapped onto one monotonic number line before subtracting,
+/// so the distance stays right across zero, where the raw bits of a
+/// negative float count the wrong way. `-0.0` and `0.0` are 0 apart.
+pub fn ulp_distance(a: f32, b: f32) -> u64 {
+    if a.is_nan() || b.is_nan() {
+        return u64::MAX;
+    }
+    ordered_bits(a).abs_diff(ordered_bits(b))
+}
+
+/// Why two floats are or aren't equal, as computed by [`explain_eq`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EqExplanation {
+    pub a: f32,
+    pub b: f32,
+    pub abs_diff: f32,
+    /// `abs_diff` relative to the larger magnitude of the two.
+    pub rel_diff: f32,
+    pub ulp_distance: u64,
+    /// The mode whose criterion decided `equal`.
+    pub mode: ComparisonMode,
+    pub equal: bool,
+}
+
+/// Computes every common measure of how far apart `a` and `b` are, along
+/// with the verdict under `mode`. Handy for figuring out why a comparison
+/// went the way it did.
+pub fn explain_eq(a: f32, b: f32, mode: ComparisonMode) -> EqExplanation {
+    let abs_diff = (a - b).abs();
+    let largest = a.abs().max(b.abs());
+    EqExplanation {
+        a,
+        b,
+        abs_diff,
+        rel_diff: if largest == 0.0 {
+            0.0
+        } else {
+            abs_diff / largest
+        },
+        ulp_distance: ulp_distance(a, b),
+        mode,
+        equal: mode.matches(a, b),
+    }
+}
+
+impl fmt::Display for EqExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = if self.equal { "equal" } else { "not equal" };
+        write!(
+            f,
+            "{} and {} are {verdict} under {:?}: ",
+            self.a, self.b, self.mode
+        )?;
+        if self.a.to_bits() == self.b.to_bits() {
+            return write!(f, "their bits are identical");
+        }
+        if self.mode == ComparisonMode::Magnitude {
+            let verdict = if self.equal { "match" } else { "differ" };
+            return write!(f, "their sign and exponent bits {verdict}");
+        }
+        if let ComparisonMode::LogBuckets(_) = self.mode {
+            let verdict = if self.equal { "share" } else { "differ in" };
+            return write!(f, "they {verdict} their logarithmic bucket");
+        }
+        let (measure, limit) = match self.mode {
+            ComparisonMode::Absolute(tolerance) => (
+                format!("|a - b| = {}", self.abs_diff),
+                tolerance.to_string(),
+            ),
+            ComparisonMode::Relative(tolerance) => (
+                format!("|a - b| = {}", self.abs_diff),
+                (tolerance * relative_scale(self.a, self.b)).to_string(),
+            ),
+            ComparisonMode::Ulps(ulps) => (
+                format!("{} ulps apart", self.ulp_distance),
+                ulps.to_string(),
+            ),
+            ComparisonMode::Magnitude | ComparisonMode::LogBuckets(_) => {
+                unreachable!("handled above")
+            }
+        };
+        let relation = if self.equal { "<=" } else { ">" };
+        write!(
+            f,
+            "{measure} {relation} {limit} (relative difference {}, {} ulps)",
+            self.rel_diff, self.ulp_distance
+        )
+    }
+}
+
+#[test]
+fn it_explains_an_absolute_match() {
+    let explanation = explain_eq(1.0, 1.000001, ComparisonMode::Absolute(0.00001));
+
+    assert!(explanation.equal);
+    assert!(explanation.abs_diff == 1.000001f32 - 1.0);
+    assert!(explanation.ulp_distance == 8);
+    assert!(explanation.to_string().contains("are equal under Absolute"));
+}
+
+#[test]
+fn it_explains_a_relative_mismatch() {
+    let explanation = explain_eq(100.0, 101.0, ComparisonMode::Relative(0.001));
+
+    assert!(!explanation.equal);
+    assert!(explanation.abs_diff == 1.0);
+    assert!(explanation.rel_diff == 1.0 / 101.0);
+    assert!(explanation.to_string().contains("not equal"));
+}
+
+#[test]
+fn it_explains_ulp_distance_across_zero() {
+    let explanation = explain_eq(
+        -f32::from_bits(1),
+        f32::from_bits(1),
+        ComparisonMode::Ulps(1),
+    );
+
+    assert!(explanation.ulp_distance == 2);
+    assert!(!explanation.equal);
+}
+
+#[test]
+fn it_validates_tolerances() {
+    for tolerance in [0.0, 0.001, f32::MAX] {
+        assert!(ComparisonMode::Absolute(tolerance).validated().is_ok());
+        assert!(ComparisonMode::Relative(tolerance).validated().is_ok());
+    }
+    for tolerance in [-0.001, f32::NAN, f32::INFINITY] {
+        let err = ComparisonMode::Absolute(tolerance).validated().unwrap_err();
+        assert!(err.tolerance.to_bits() == tolerance.to_bits());
+        assert!(ComparisonMode::Relative(tolerance).validated().is_err());
+    }
+    assert!(ComparisonMode::Ulps(0).validated().is_ok());
+    assert!(validated_tolerance(f64::MAX) == Ok(f64::MAX));
+    assert!(validated_tolerance(-1e-300f64).is_err());
+    assert!(validated_tolerance(f64::NEG_INFINITY).is_err());
+}
+
+#[test]
+fn it_measures_ulp_distance() {
+    let tiny = f32::from_bits(1);
+
+    assert!(ulp_distance(-0.0, 0.0) == 0);
+    assert!(ulp_distance(1.0, 1.0f32.next_up()) == 1);
+    assert!(ulp_distance(-1.0, -1.0f32.next_down()) == 1);
+    assert!(ulp_distance(tiny, -tiny) == 2);
+    assert!(ulp_distance(-tiny, 0.0) == 1);
+    assert!(ulp_distance(-1.0, 1.0) == 2 * u64::from(1.0f32.to_bits()));
+    assert!(ulp_distance(f32::NAN, 1.0) == u64::MAX);
+}
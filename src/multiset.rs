@@ -0,0 +1,60 @@
+use crate::ApproxHashMap;
+
+/// Counts occurrences of approximately-equal `f32`s instead of deduping them.
+///
+/// Like [`crate::FloatHashSet`], each cluster is keyed by the first value
+/// inserted into it, and later values within tolerance bump its count.
+#[derive(Debug, Clone)]
+pub struct FloatMultiSet {
+    counts: ApproxHashMap<f32, usize>,
+}
+
+impl FloatMultiSet {
+    pub fn new(tolerance: f32) -> Self {
+        FloatMultiSet {
+            counts: ApproxHashMap::new(tolerance),
+        }
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.counts.tolerance()
+    }
+
+    /// Number of clusters.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Records one occurrence of `value`, returning its cluster's new count.
+    pub fn insert(&mut self, value: f32) -> usize {
+        if let Some(count) = self.counts.get_mut(value) {
+            *count += 1;
+            return *count;
+        }
+        self.counts.insert(value, 1);
+        1
+    }
+
+    /// Occurrences recorded for the cluster within tolerance of `value`.
+    pub fn count(&self, value: f32) -> usize {
+        self.counts.get(value).copied().unwrap_or(0)
+    }
+}
+
+#[test]
+fn it_counts_near_equal_values_as_one_cluster() {
+    let mut set = FloatMultiSet::new(0.00001);
+    set.insert(42.0);
+    set.insert(42.000004);
+    set.insert(41.999996);
+    set.insert(7.0);
+
+    assert!(set.len() == 2);
+    assert!(set.count(42.0) == 3);
+    assert!(set.count(7.0) == 1);
+    assert!(set.count(8.0) == 0);
+}
@@ -0,0 +1,96 @@
+/// A set of closed `f32` intervals where ranges that overlap, or are
+/// separated by a gap of at most `tolerance`, are merged into one.
+///
+/// The intervals are kept sorted and disjoint, so lookups are a binary
+/// search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatRangeSet {
+    tolerance: f32,
+    ranges: Vec<(f32, f32)>,
+}
+
+impl FloatRangeSet {
+    pub fn new(tolerance: f32) -> Self {
+        FloatRangeSet {
+            tolerance,
+            ranges: Vec::new(),
+        }
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Number of disjoint intervals.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Adds the interval `[lo, hi]` (in either order), merging it with every
+    /// stored interval it overlaps or comes within tolerance of. Intervals
+    /// with a NaN bound are ignored.
+    pub fn insert(&mut self, lo: f32, hi: f32) {
+        if lo.is_nan() || hi.is_nan() {
+            return;
+        }
+        let (mut lo, mut hi) = if lo <= hi { (lo, hi) } else { (hi, lo) };
+
+        let start = self
+            .ranges
+            .partition_point(|(_, stored_hi)| lo - stored_hi > self.tolerance);
+        let end = self
+            .ranges
+            .partition_point(|(stored_lo, _)| stored_lo - hi <= self.tolerance);
+        if start < end {
+            lo = lo.min(self.ranges[start].0);
+            hi = hi.max(self.ranges[end - 1].1);
+        }
+        self.ranges.splice(start..end, [(lo, hi)]);
+    }
+
+    /// Whether `value` lies within tolerance of a stored interval.
+    pub fn contains(&self, value: f32) -> bool {
+        let idx = self
+            .ranges
+            .partition_point(|(_, stored_hi)| value - stored_hi > self.tolerance);
+        self.ranges
+            .get(idx)
+            .is_some_and(|(stored_lo, _)| stored_lo - value <= self.tolerance)
+    }
+
+    /// The intervals as `(lo, hi)` pairs, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.ranges.iter().copied()
+    }
+}
+
+#[test]
+fn it_merges_touching_intervals() {
+    let mut set = FloatRangeSet::new(0.00001);
+    set.insert(1.0, 2.0);
+    set.insert(2.000005, 3.0);
+
+    assert!(set.iter().collect::<Vec<_>>() == vec![(1.0, 3.0)]);
+    assert!(set.contains(2.000002));
+    assert!(!set.contains(3.1));
+}
+
+#[test]
+fn it_bridges_several_intervals_at_once() {
+    let mut set = FloatRangeSet::new(0.1);
+    set.insert(5.0, 6.0);
+    set.insert(1.0, 2.0);
+    set.insert(3.0, 4.0);
+    assert!(set.len() == 3);
+    assert!(!set.contains(2.5));
+
+    set.insert(4.05, 1.95);
+
+    assert!(set.iter().collect::<Vec<_>>() == vec![(1.0, 4.05), (5.0, 6.0)]);
+    assert!(set.contains(0.95));
+    assert!(!set.contains(4.5));
+}
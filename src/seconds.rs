@@ -0,0 +1,53 @@
+#[cfg(test)]
+use std::collections::HashSet;
+
+use crate::F32Wrapper;
+
+/// A time in `f32` seconds, e.g. an event timestamp, that compares and
+/// hashes on a grid whose tolerance is given in milliseconds.
+///
+/// It's an [`F32Wrapper`] with an absolute tolerance underneath, so the same
+/// caveats apply: equality means sharing a grid cell, and timestamps within
+/// tolerance of each other on either side of a cell edge are unequal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SecondsWrapper(F32Wrapper);
+
+impl SecondsWrapper {
+    pub fn from_secs(secs: f32, tolerance_ms: f32) -> Self {
+        SecondsWrapper(F32Wrapper::with_tolerance(secs, tolerance_ms / 1000.0))
+    }
+
+    pub fn from_millis(millis: f32, tolerance_ms: f32) -> Self {
+        Self::from_secs(millis / 1000.0, tolerance_ms)
+    }
+
+    pub fn secs(self) -> f32 {
+        self.0.value()
+    }
+
+    pub fn millis(self) -> f32 {
+        self.0.value() * 1000.0
+    }
+
+    pub fn tolerance_ms(self) -> f32 {
+        self.0.tolerance() * 1000.0
+    }
+
+    /// The wrapper the time is compared as, in seconds.
+    pub fn as_wrapper(self) -> F32Wrapper {
+        self.0
+    }
+}
+
+#[test]
+fn it_dedups_timestamps_within_the_tolerance() {
+    let timestamps = [1.0012, 1.0014, 2.5, 1.0458, 2.5003];
+    let set: HashSet<_> = timestamps
+        .iter()
+        .map(|secs| SecondsWrapper::from_secs(*secs, 1.0))
+        .collect();
+
+    assert!(set.len() == 3);
+    assert!(set.contains(&SecondsWrapper::from_millis(1001.0, 1.0)));
+    assert!(!set.contains(&SecondsWrapper::from_millis(1010.0, 1.0)));
+}
@@ -0,0 +1,1054 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+use std::io::{self, Read};
+use std::sync::OnceLock;
+
+use crate::map::{Entry, IntegrityError, IntoEntries};
+use crate::{
+    ApproxHashMap, ComparisonMode, F32Wrapper, FixedState, FloatStats, FrozenFloatSet,
+    ToleranceError, F32_ERROR_TOLERANCE,
+};
+
+/// A set of `f32`s where values within `tolerance` of a stored value count
+/// as already present.
+///
+/// Each stored value is the representative of its cluster: the first value
+/// inserted that wasn't within tolerance of anything already there. Lookups
+/// probe neighbouring grid cells (see [`ApproxHashMap`]), so a value within
+/// tolerance of a representative is always found, even across a cell edge.
+///
+/// Each cluster also counts the values merged into it, which
+/// [`FloatHashSet::retain_clusters`] can filter on.
+#[derive(Debug, Clone)]
+pub struct FloatHashSet<S = RandomState> {
+    /// Representative -> number of values merged into its cluster
+    map: ApproxHashMap<f32, usize, S>,
+    /// The representatives but NaN in ascending order, for range queries.
+    /// Built by the first one and dropped whenever membership changes.
+    sorted: OnceLock<Vec<f32>>,
+}
+
+/// Which value stands for a cluster when several are merged into it, for
+/// [`FloatHashSet::from_iter_with`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RepresentativePolicy {
+    /// The first value of the cluster, as [`FloatHashSet::insert`] keeps.
+    #[default]
+    KeepFirst,
+    /// The last value merged into the cluster.
+    KeepLast,
+    /// The mean of every value merged into the cluster.
+    KeepMean,
+}
+
+/// How two sets' representatives line up, from
+/// [`FloatHashSet::tolerant_diff`]. Everything is sorted ascending.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TolerantDiff {
+    /// Representatives with nothing in the other set within tolerance.
+    pub only_in_self: Vec<f32>,
+    /// The other set's representatives with nothing here within tolerance.
+    pub only_in_other: Vec<f32>,
+    /// `(ours, theirs)` pairs that matched within tolerance without being
+    /// bit-identical.
+    pub near_matches: Vec<(f32, f32)>,
+}
+
+impl FloatHashSet<RandomState> {
+    /// Creates an empty set. `tolerance` must be finite and not negative,
+    /// which debug builds assert.
+    pub fn new(tolerance: f32) -> Self {
+        Self::with_hasher(tolerance, RandomState::new())
+    }
+
+    /// Like [`FloatHashSet::new`], but returns an error for a negative, NaN,
+    /// or infinite tolerance in every build.
+    pub fn try_new(tolerance: f32) -> Result<Self, ToleranceError> {
+        ComparisonMode::Absolute(tolerance).validated()?;
+        Ok(Self::new(tolerance))
+    }
+
+    /// Builds a set from `values` in one go, with `policy` picking each
+    /// cluster's representative.
+    ///
+    /// Values are clustered just as by repeated inserts, against the first
+    /// value of each cluster. The chosen representatives are then inserted
+    /// in ascending order, so if moving them (to the last value or to the
+    /// mean) brings two within tolerance of each other, they merge into the
+    /// lower one.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub fn from_iter_with<I: IntoIterator<Item = f32>>(
+        values: I,
+        tolerance: f32,
+        policy: RepresentativePolicy,
+    ) -> Self {
+        // first value -> (last value, running sum, count)
+        let mut clusters: ApproxHashMap<f32, (f32, f64, usize)> = ApproxHashMap::new(tolerance);
+        for val in values {
+            let cluster = clusters.entry(val).or_insert((val, 0.0, 0));
+            cluster.0 = val;
+            cluster.1 += f64::from(val);
+            cluster.2 += 1;
+        }
+
+        let mut representatives: Vec<(f32, usize)> = clusters
+            .into_entries()
+            .map(|(first, (last, sum, count))| {
+                let representative = match policy {
+                    RepresentativePolicy::KeepFirst => first,
+                    RepresentativePolicy::KeepLast => last,
+                    RepresentativePolicy::KeepMean => (sum / count as f64) as f32,
+                };
+                (representative, count)
+            })
+            .collect();
+        representatives.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let mut set = FloatHashSet::new(tolerance);
+        for (representative, count) in representatives {
+            set.add(representative, count);
+        }
+        set
+    }
+
+    /// Builds a set from `values` as by repeated inserts, also returning how
+    /// many of them were merged into an existing cluster rather than starting
+    /// one. That's always the number of values minus the set's length.
+    pub fn from_iter_counting<I: IntoIterator<Item = f32>>(
+        values: I,
+        tolerance: f32,
+    ) -> (Self, usize) {
+        let mut set = FloatHashSet::new(tolerance);
+        let mut merged = 0;
+        for val in values {
+            if !set.add(val, 1) {
+                merged += 1;
+            }
+        }
+        (set, merged)
+    }
+
+    /// Rebuilds a set from the representatives' bits, as exported by
+    /// [`FloatHashSet::to_canonical_bits`]. Stored representatives are never
+    /// within tolerance of each other, so at the same tolerance each one
+    /// starts its own cluster again and the membership comes back identical.
+    /// Counts aren't kept; every cluster comes back with a count of 1.
+    pub fn from_canonical_bits(bits: &[u32], tolerance: f32) -> Self {
+        let mut set = FloatHashSet::new(tolerance);
+        set.map.reserve(bits.len());
+        for val in bits {
+            set.add(f32::from_bits(*val), 1);
+        }
+        set
+    }
+
+    /// Streams raw `f32`s out of `reader` into a new set, 4 little-endian
+    /// bytes each (what `f32::to_le_bytes` writes), inserting as they come so
+    /// the input is never held in memory at once. Bytes are read in blocks,
+    /// so short reads are fine and unbuffered readers don't need wrapping.
+    ///
+    /// Fails with the reader's own error, or with
+    /// [`io::ErrorKind::UnexpectedEof`] if the input stops partway through a
+    /// value. Big-endian data needs its bytes swapped beforehand.
+    pub fn from_reader<R: Read>(mut reader: R, tolerance: f32) -> io::Result<Self> {
+        const SIZE: usize = std::mem::size_of::<f32>();
+        let mut set = FloatHashSet::new(tolerance);
+        let mut buf = [0u8; 4096];
+        let mut filled = 0;
+        loop {
+            let read = match reader.read(&mut buf[filled..]) {
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if read == 0 {
+                break;
+            }
+            filled += read;
+            let whole = filled - filled % SIZE;
+            for bytes in buf[..whole].chunks_exact(SIZE) {
+                set.add(f32::from_le_bytes(bytes.try_into().unwrap()), 1);
+            }
+            buf.copy_within(whole..filled, 0);
+            filled -= whole;
+        }
+        if filled != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("input ended {filled} bytes into an f32"),
+            ));
+        }
+        Ok(set)
+    }
+
+    /// Adopts the members of a hand-built `HashSet<F32Wrapper>`, clustered
+    /// at `tolerance` from here on whatever the wrappers' own tolerances.
+    /// Members are added in ascending order, so if `tolerance` is wider than
+    /// theirs the smallest of each newly-close group is kept.
+    pub fn from_wrapper_set<H>(set: HashSet<F32Wrapper, H>, tolerance: f32) -> Self {
+        let mut values: Vec<f32> = set.into_iter().map(F32Wrapper::value).collect();
+        values.sort_by(f32::total_cmp);
+
+        let mut adopted = FloatHashSet::new(tolerance);
+        adopted.extend(values);
+        adopted
+    }
+}
+
+impl FloatHashSet<FixedState> {
+    /// A set whose hasher is keyed only by `seed`, so that the same inserts
+    /// iterate in the same order on every run. Useful for reproducible tests
+    /// and benchmarks; see [`FixedState`] for the caveats.
+    pub fn with_fixed_seed(tolerance: f32, seed: u64) -> Self {
+        Self::with_hasher(tolerance, FixedState::with_seed(seed))
+    }
+}
+
+impl<S: BuildHasher> FloatHashSet<S> {
+    pub fn with_hasher(tolerance: f32, hash_builder: S) -> Self {
+        Self::with_capacity_and_hasher(tolerance, 0, hash_builder)
+    }
+
+    /// Creates an empty set with room for at least `capacity` clusters
+    /// before reallocating (as long as they land in distinct grid cells),
+    /// hashing them with `hash_builder`.
+    pub fn with_capacity_and_hasher(tolerance: f32, capacity: usize, hash_builder: S) -> Self {
+        FloatHashSet {
+            map: ApproxHashMap::with_capacity_and_hasher(tolerance, capacity, hash_builder),
+            sorted: OnceLock::new(),
+        }
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.map.tolerance()
+    }
+
+    /// Number of clusters (not of values inserted).
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Adds `value` as a new cluster. Returns `false` if it was within
+    /// tolerance of an existing representative, which is left unchanged
+    /// (though its cluster's count goes up).
+    pub fn insert(&mut self, value: f32) -> bool {
+        self.add(value, 1)
+    }
+
+    /// Like [`FloatHashSet::insert`], but on a match hands back the existing
+    /// representative `value` merged into.
+    pub fn try_insert(&mut self, value: f32) -> Result<(), f32> {
+        match self.get(value) {
+            Some(existing) => {
+                self.add(value, 1);
+                Err(existing)
+            }
+            None => {
+                self.map.insert(value, 1);
+                self.sorted.take();
+                Ok(())
+            }
+        }
+    }
+
+    /// Inserts every value in `values`, in order, returning how many new
+    /// clusters were created. Ends up with the same membership as inserting
+    /// one at a time, and does just that after reserving a cell per value up
+    /// front, so the only saving is the rehashing. Values aren't grouped by
+    /// cell first: a value can merge into a neighbouring cell's cluster, so
+    /// reordering them could change which one becomes the representative.
+    pub fn insert_many(&mut self, values: &[f32]) -> usize {
+        self.map.reserve(values.len());
+        values.iter().filter(|val| self.add(**val, 1)).count()
+    }
+
+    /// Adds `count` values at `value` to its cluster, returning whether that
+    /// started a new one.
+    fn add(&mut self, value: f32, count: usize) -> bool {
+        match self.map.entry(value) {
+            Entry::Occupied(mut cluster) => {
+                *cluster.get_mut() += count;
+                false
+            }
+            Entry::Vacant(cluster) => {
+                cluster.insert(count);
+                self.sorted.take();
+                true
+            }
+        }
+    }
+
+    /// Number of values merged into the cluster within tolerance of `value`,
+    /// counting its representative.
+    pub fn count(&self, value: f32) -> usize {
+        self.map.get(value).copied().unwrap_or(0)
+    }
+
+    /// Keeps only the clusters `keep` returns `true` for, given each one's
+    /// representative and count. E.g. `|_, count| count >= 2` drops outliers
+    /// that never had company.
+    pub fn retain_clusters<K: FnMut(f32, usize) -> bool>(&mut self, mut keep: K) {
+        self.map
+            .retain(|representative, count| keep(representative, *count));
+        self.sorted.take();
+    }
+
+    /// Keeps only the representatives `keep` returns `true` for, returning
+    /// how many were dropped.
+    pub fn retain_count<K: FnMut(f32) -> bool>(&mut self, mut keep: K) -> usize {
+        let before = self.len();
+        self.map.retain(|representative, _| keep(representative));
+        self.sorted.take();
+        before - self.len()
+    }
+
+    pub fn contains(&self, value: f32) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// The representative numerically closest to `query`, whether or not
+    /// it's within tolerance. `None` only if the set holds nothing to
+    /// compare against: it's empty, or `query` is NaN and the set holds no
+    /// NaN (NaN is only ever nearest to NaN).
+    pub fn nearest(&self, query: f32) -> Option<f32> {
+        self.map.nearest_key(query)
+    }
+
+    /// Tolerant membership of each of `queries`, in order.
+    pub fn which_present(&self, queries: &[f32]) -> Vec<bool> {
+        queries.iter().map(|val| self.contains(*val)).collect()
+    }
+
+    /// Whether every one of `queries` is within tolerance of a member.
+    pub fn contains_all(&self, queries: &[f32]) -> bool {
+        queries.iter().all(|val| self.contains(*val))
+    }
+
+    /// Number of representatives strictly below `threshold`.
+    ///
+    /// The first query sorts the representatives, and later ones binary
+    /// search that until an insert or removal changes the membership, so a
+    /// run of queries against an unchanging set costs `O(log n)` each.
+    pub fn count_below(&self, threshold: f32) -> usize {
+        self.sorted()
+            .partition_point(|representative| *representative < threshold)
+    }
+
+    /// Number of representatives strictly above `threshold`; see
+    /// [`FloatHashSet::count_below`].
+    pub fn count_above(&self, threshold: f32) -> usize {
+        if threshold.is_nan() {
+            return 0;
+        }
+        let sorted = self.sorted();
+        sorted.len() - sorted.partition_point(|representative| *representative <= threshold)
+    }
+
+    fn sorted(&self) -> &[f32] {
+        self.sorted.get_or_init(|| {
+            let mut sorted: Vec<f32> = self.iter().filter(|val| !val.is_nan()).collect();
+            sorted.sort_by(f32::total_cmp);
+            sorted
+        })
+    }
+
+    /// The representative within tolerance of `value`, if any.
+    pub fn get(&self, value: f32) -> Option<f32> {
+        self.map.get_key_value(value).map(|(stored, _)| stored)
+    }
+
+    pub fn remove(&mut self, value: f32) -> bool {
+        self.take(value).is_some()
+    }
+
+    /// Removes and returns the representative within tolerance of `value`,
+    /// mirroring [`std::collections::HashSet::take`].
+    pub fn take(&mut self, value: f32) -> Option<f32> {
+        let (stored, _) = self.map.remove_entry(value)?;
+        self.sorted.take();
+        Some(stored)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.sorted.take();
+    }
+
+    /// See [`ApproxHashMap::shrink_to_fit`].
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit();
+    }
+
+    /// Estimated bytes allocated; see [`ApproxHashMap::memory_footprint`].
+    pub fn memory_footprint(&self) -> usize {
+        self.map.memory_footprint()
+    }
+
+    /// The representatives, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.map.keys()
+    }
+
+    /// Converts into a read-only [`FrozenFloatSet`] of the same
+    /// representatives, which is smaller and looks values up by binary
+    /// search. Counts are dropped.
+    pub fn freeze(self) -> FrozenFloatSet {
+        let tolerance = self.tolerance();
+        FrozenFloatSet::from_representatives(self.map.into_entries().map(|(val, _)| val), tolerance)
+    }
+
+    /// Every cluster as `(representative, count)`, sorted ascending by
+    /// representative.
+    pub fn export_sorted(&self) -> Vec<(f32, usize)> {
+        let mut clusters: Vec<(f32, usize)> = self
+            .map
+            .iter()
+            .map(|(representative, count)| (representative, *count))
+            .collect();
+        clusters.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        clusters
+    }
+
+    /// The representatives' raw bits, in ascending order of value, for
+    /// persisting the set; see [`FloatHashSet::from_canonical_bits`].
+    pub fn to_canonical_bits(&self) -> Vec<u32> {
+        self.export_sorted()
+            .into_iter()
+            .map(|(representative, _)| representative.to_bits())
+            .collect()
+    }
+
+    /// Count, extremes, mean, and standard deviation of the representatives
+    /// (not of every value inserted). An empty set has NaN for everything
+    /// but the count. See [`FloatStats`].
+    pub fn statistics(&self) -> FloatStats {
+        FloatStats::from_values(self.iter())
+    }
+
+    /// Re-clusters the representatives at `tolerance`, inserting them in
+    /// ascending order so the result doesn't depend on iteration order.
+    ///
+    /// Only representatives survive a set, not the values merged into them,
+    /// so coarsening can merge clusters (adding up their counts) but refining
+    /// can never split one back apart.
+    pub fn rescale(self, tolerance: f32) -> FloatHashSet<S>
+    where
+        S: Clone,
+    {
+        let mut rescaled = FloatHashSet::with_hasher(tolerance, self.map.hasher().clone());
+        let mut clusters: Vec<(f32, usize)> = self.map.into_entries().collect();
+        clusters.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        for (representative, count) in clusters {
+            rescaled.add(representative, count);
+        }
+        rescaled
+    }
+
+    /// The representatives of both sets, keeping `self`'s where the two
+    /// overlap. `other`'s are merged in ascending order, so the result doesn't
+    /// depend on iteration order, and overlapping clusters add up their
+    /// counts. The result uses `self`'s tolerance and hasher.
+    ///
+    /// Special values follow the map's rules: every NaN, whatever its sign
+    /// or payload, collapses into a single member, `inf` and `-inf` are one
+    /// member each, and finite values are merged by tolerance as usual.
+    pub fn union<T: BuildHasher>(&self, other: &FloatHashSet<T>) -> FloatHashSet<S>
+    where
+        S: Clone,
+    {
+        let mut added: Vec<(f32, usize)> =
+            other.map.iter().map(|(val, count)| (val, *count)).collect();
+        added.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let mut union = self.clone();
+        for (val, count) in added {
+            union.add(val, count);
+        }
+        union
+    }
+
+    /// A copy of the set to [`FloatHashSet::restore`] later, e.g. for undo.
+    pub fn snapshot(&self) -> FloatHashSet<S>
+    where
+        S: Clone,
+    {
+        self.clone()
+    }
+
+    /// Replaces the contents (and tolerance) with those of `snapshot`.
+    pub fn restore(&mut self, snapshot: FloatHashSet<S>) {
+        *self = snapshot;
+    }
+
+    /// Compares against an older snapshot, returning the representatives
+    /// `added` since then and those `removed`, each sorted ascending. A
+    /// representative only counts as changed if nothing on the other side is
+    /// within tolerance of it, so re-inserting a near-duplicate isn't a diff.
+    pub fn diff<T: BuildHasher>(&self, other: &FloatHashSet<T>) -> (Vec<f32>, Vec<f32>) {
+        fn missing_from<S: BuildHasher, T: BuildHasher>(
+            from: &FloatHashSet<S>,
+            other: &FloatHashSet<T>,
+        ) -> Vec<f32> {
+            let mut missing: Vec<f32> = from
+                .map
+                .keys()
+                .filter(|val| !other.contains(*val))
+                .collect();
+            missing.sort_by(f32::total_cmp);
+            missing
+        }
+        (missing_from(self, other), missing_from(other, self))
+    }
+
+    /// Checks that no two representatives are within tolerance of each
+    /// other, along with the rest of [`ApproxHashMap::validate`]'s table
+    /// invariants. A failure means a bucketing bug rather than anything the
+    /// caller did, so this is for debugging, e.g. after a long run of
+    /// inserts and removals.
+    pub fn validate(&self) -> Result<(), IntegrityError> {
+        self.map.validate()
+    }
+
+    /// Like [`FloatHashSet::diff`], but also reports the representatives
+    /// that only matched the other set within tolerance, paired with what
+    /// they matched, rather than counting those as the same.
+    pub fn tolerant_diff<T: BuildHasher>(&self, other: &FloatHashSet<T>) -> TolerantDiff {
+        let (only_in_self, only_in_other) = self.diff(other);
+        let mut near_matches: Vec<(f32, f32)> = self
+            .map
+            .keys()
+            .filter_map(|ours| Some((ours, other.get(ours)?)))
+            .filter(|(ours, theirs)| ours.to_bits() != theirs.to_bits())
+            .collect();
+        near_matches.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        TolerantDiff {
+            only_in_self,
+            only_in_other,
+            near_matches,
+        }
+    }
+}
+
+/// Yields the representatives, in arbitrary order.
+impl<S: BuildHasher> IntoIterator for FloatHashSet<S> {
+    type Item = f32;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            entries: self.map.into_entries(),
+        }
+    }
+}
+
+pub struct IntoIter {
+    entries: IntoEntries<f32, usize>,
+}
+
+impl Iterator for IntoIter {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.entries.next().map(|(value, _)| value)
+    }
+}
+
+impl<S: BuildHasher> Extend<f32> for FloatHashSet<S> {
+    fn extend<I: IntoIterator<Item = f32>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// Infers the tolerance as the widest absolute tolerance among the members
+/// (`F32_ERROR_TOLERANCE` if there are none); use
+/// [`FloatHashSet::from_wrapper_set`] to pick one instead. Either way, that
+/// tolerance governs all later operations.
+impl<H> From<HashSet<F32Wrapper, H>> for FloatHashSet {
+    fn from(set: HashSet<F32Wrapper, H>) -> Self {
+        let tolerance = set
+            .iter()
+            .map(|val| val.tolerance())
+            .reduce(f32::max)
+            .unwrap_or(F32_ERROR_TOLERANCE);
+        FloatHashSet::from_wrapper_set(set, tolerance)
+    }
+}
+
+/// The set's tolerance wins: a wrapper contributes only its value, and is
+/// clustered exactly as if that raw `f32` had been inserted. A set can only
+/// have one grid, so honoring each wrapper's own tolerance isn't an option.
+impl<S: BuildHasher> Extend<F32Wrapper> for FloatHashSet<S> {
+    fn extend<I: IntoIterator<Item = F32Wrapper>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(F32Wrapper::value));
+    }
+}
+
+#[test]
+fn it_keeps_the_first_representative() {
+    let mut set = FloatHashSet::new(0.00001);
+
+    assert!(set.insert(42.0));
+    assert!(!set.insert(42.000004));
+    assert!(set.len() == 1);
+    assert!(set.get(42.000004) == Some(42.0));
+}
+
+#[test]
+fn it_reports_the_matched_representative_on_try_insert() {
+    let tolerance = 0.00001;
+    let mut set = FloatHashSet::new(tolerance);
+
+    assert!(set.try_insert(42.0) == Ok(()));
+    assert!(set.try_insert(42.0 + tolerance / 2.0) == Err(42.0));
+    assert!(set.len() == 1);
+}
+
+#[test]
+fn it_inserts_batches_like_single_inserts() {
+    let values: Vec<f32> = (0..1000u16).map(|i| f32::from(i % 300) * 0.37).collect();
+    let mut single = FloatHashSet::new(0.001);
+    let new_clusters = values.iter().filter(|val| single.insert(**val)).count();
+    let mut batch = FloatHashSet::new(0.001);
+
+    assert!(batch.insert_many(&values) == new_clusters);
+    assert!(batch.insert_many(&values) == 0);
+    assert!(batch.len() == single.len());
+    assert!(single.iter().all(|val| batch.get(val) == Some(val)));
+}
+
+#[test]
+fn it_checks_batches_of_queries() {
+    let mut set = FloatHashSet::new(0.00001);
+    set.extend([1.0, 2.0, 3.0]);
+
+    assert!(set.which_present(&[2.000004, 5.0, 1.0, 2.5]) == vec![true, false, true, false]);
+    assert!(set.contains_all(&[0.999996, 3.0]));
+    assert!(!set.contains_all(&[1.0, 4.0]));
+    assert!(set.contains_all(&[]));
+}
+
+#[test]
+fn it_shrinks_after_bulk_removals() {
+    let mut set = FloatHashSet::new(0.001);
+    set.extend((0..10_000u16).map(f32::from));
+    for val in 10..10_000u16 {
+        set.remove(f32::from(val));
+    }
+    let before = set.memory_footprint();
+
+    set.shrink_to_fit();
+
+    assert!(set.memory_footprint() < before);
+    assert!(set.len() == 10);
+    assert!(set.contains(9.0));
+}
+
+#[test]
+fn it_counts_representatives_around_thresholds() {
+    let mut set = FloatHashSet::new(0.00001);
+    set.extend((0..100u8).map(|i| f32::from(i) * 0.5));
+
+    assert!(set.count_below(10.0) == 20);
+    assert!(set.count_above(10.0) == 79);
+    assert!(set.count_below(0.0) == 0);
+    assert!(set.count_above(49.5) == 0);
+    assert!(set.count_below(f32::NAN) == 0);
+    assert!(set.count_above(f32::NAN) == 0);
+
+    // Each change to the membership has to show up in the next count
+    set.insert(f32::NAN);
+    set.insert(-3.0);
+    assert!(set.count_below(10.0) == 21);
+    set.remove(0.0);
+    set.take(0.5);
+    assert!(set.count_below(10.0) == 19);
+    set.retain_count(|val| val < 40.0);
+    assert!(set.count_above(10.0) == 59);
+    set.clear();
+    assert!(set.count_above(-10.0) == 0);
+}
+
+#[test]
+fn it_retains_clusters_by_count() {
+    let mut set = FloatHashSet::new(0.01);
+    set.extend([1.0, 1.005, 0.998, 5.0, 7.0, 7.003, 9.5]);
+    assert!(set.count(1.0) == 3);
+    assert!(set.count(7.001) == 2);
+
+    set.retain_clusters(|_, count| count >= 2);
+
+    let mut kept: Vec<f32> = set.iter().collect();
+    kept.sort_by(f32::total_cmp);
+    assert!(kept == vec![1.0, 7.0]);
+    assert!(set.len() == 2);
+}
+
+#[test]
+fn it_exports_sorted_occupancy() {
+    let mut set = FloatHashSet::new(0.001);
+    set.extend([3.0, -1.0, 3.0005, 0.5, -0.9995, 3.0001]);
+
+    assert!(set.export_sorted() == vec![(-1.0, 2), (0.5, 1), (3.0, 3)]);
+}
+
+#[test]
+fn it_takes_representatives_via_near_matches() {
+    let mut set = FloatHashSet::new(0.5);
+    set.insert(1.0);
+    set.insert(3.0);
+
+    // 1.4 is in a different grid cell than 1.0 but still within tolerance
+    assert!(set.take(1.4) == Some(1.0));
+    assert!(set.take(1.4).is_none());
+    assert!(set.len() == 1);
+    assert!(set.contains(3.0));
+}
+
+#[test]
+fn it_extends_with_wrappers_at_the_sets_tolerance() {
+    let mut set = FloatHashSet::new(0.1);
+    set.extend([1.0, 1.05, 2.0].map(|val| F32Wrapper::with_tolerance(val, 0.00001)));
+
+    assert!(set.len() == 2);
+    assert!(set.get(1.05) == Some(1.0));
+    assert!(set.contains(2.08));
+}
+
+#[test]
+fn it_builds_sets_with_a_representative_policy() {
+    let values = [1.0, 1.06, 1.08, 5.0, 4.96];
+    let sorted = |set: FloatHashSet| {
+        let mut values: Vec<f32> = set.into_iter().collect();
+        values.sort_by(f32::total_cmp);
+        values
+    };
+
+    let first = FloatHashSet::from_iter_with(values, 0.1, RepresentativePolicy::KeepFirst);
+    assert!(sorted(first) == vec![1.0, 5.0]);
+    let last = FloatHashSet::from_iter_with(values, 0.1, RepresentativePolicy::KeepLast);
+    assert!(sorted(last) == vec![1.08, 4.96]);
+    let mean = FloatHashSet::from_iter_with(values, 0.1, RepresentativePolicy::KeepMean);
+    let mean = sorted(mean);
+    assert!(mean.len() == 2);
+    assert!((mean[0] - 1.0466667).abs() < 1e-6);
+    assert!((mean[1] - 4.98).abs() < 1e-6);
+}
+
+#[test]
+fn it_adopts_hand_built_wrapper_sets() {
+    let manual: HashSet<F32Wrapper> = [1.0, 2.0, 3.5].map(F32Wrapper::new).into();
+
+    let set = FloatHashSet::from(manual.clone());
+    assert!(set.tolerance() == F32_ERROR_TOLERANCE);
+    assert!(set.len() == 3);
+    assert!(set.get(2.000004) == Some(2.0));
+
+    let coarse = FloatHashSet::from_wrapper_set(manual, 0.5);
+    assert!(coarse.get(3.9) == Some(3.5));
+}
+
+#[test]
+fn it_restores_and_diffs_snapshots() {
+    let mut set = FloatHashSet::new(0.00001);
+    set.extend([1.0, 2.0, 3.0]);
+    let snapshot = set.snapshot();
+
+    set.remove(2.0);
+    set.insert(4.0);
+    set.insert(3.000004);
+
+    assert!(set.diff(&snapshot) == (vec![4.0], vec![2.0]));
+
+    set.restore(snapshot);
+    assert!(set.len() == 3);
+    assert!(set.contains(2.0));
+    assert!(!set.contains(4.0));
+}
+
+#[test]
+fn it_unions_special_values_into_one_member_each() {
+    let mut a = FloatHashSet::new(0.00001);
+    a.extend([f32::NAN, f32::INFINITY, 1.0, 2.0]);
+    let mut b = FloatHashSet::new(0.00001);
+    b.extend([-f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 1.000004, 3.0]);
+
+    let union = a.union(&b);
+
+    assert!(union.len() == 6);
+    assert!(union.iter().filter(|val| val.is_nan()).count() == 1);
+    assert!(union.contains(f32::NEG_INFINITY));
+    assert!(union.get(1.000004) == Some(1.0));
+    assert!(union.contains(3.0));
+}
+
+#[test]
+fn it_summarizes_representatives() {
+    let mut set = FloatHashSet::new(0.00001);
+    set.extend([1.0, 2.0, 3.0, 4.0, 2.000004]);
+
+    let stats = set.statistics();
+
+    assert!(stats.count == 4);
+    assert!(stats.min == 1.0 && stats.max == 4.0);
+    assert!(stats.mean == 2.5);
+    assert!(stats.std_dev == 1.25f32.sqrt());
+    assert!(FloatHashSet::new(0.1).statistics().mean.is_nan());
+}
+
+#[test]
+fn it_rescales_to_coarser_and_finer_tolerances() {
+    let mut set = FloatHashSet::new(0.1);
+    set.extend([1.0, 1.2, 1.4, 3.0]);
+    assert!(set.len() == 4);
+
+    let coarse = set.rescale(0.5);
+    assert!(coarse.len() == 2);
+    assert!(coarse.get(1.4) == Some(1.0));
+
+    // 1.2 and 1.4 were merged into 1.0 and don't come back
+    let fine = coarse.rescale(0.01);
+    assert!(fine.len() == 2);
+    assert!(!fine.contains(1.2));
+}
+
+#[test]
+fn it_only_merges_identical_values_at_zero_tolerance() {
+    let mut set = FloatHashSet::new(0.0);
+    set.extend([1.0, 1.0, 1.0f32.next_up(), -0.0, 0.0]);
+
+    assert!(set.len() == 3);
+    assert!(set.contains(1.0f32.next_up()));
+    assert!(!set.contains(1.0f32.next_down()));
+}
+
+#[test]
+fn it_merges_half_a_tolerance_either_side_of_zero_with_zero() {
+    let tolerance = 0.00001;
+    for order in [[0.0, 0.000005, -0.000005], [0.000005, -0.000005, -0.0]] {
+        let mut set = FloatHashSet::new(tolerance);
+        set.extend(order);
+
+        assert!(set.len() == 1);
+        assert!(set.count(0.0) == 3);
+        assert!(set.contains(-0.000005) && set.contains(0.000005));
+    }
+}
+
+#[test]
+fn it_rejects_negative_and_nan_tolerances() {
+    assert!(FloatHashSet::try_new(-1.0).is_err());
+    assert!(FloatHashSet::try_new(f32::NAN).is_err());
+    assert!(FloatHashSet::try_new(0.0).is_ok());
+    assert!(FloatHashSet::try_new(0.001).unwrap().tolerance() == 0.001);
+}
+
+#[test]
+fn it_counts_values_merged_while_building() {
+    let (set, merged) = FloatHashSet::from_iter_counting(
+        [1.0, 1.000004, 2.0, 0.999995, 3.0, 2.000001, 1.0],
+        0.00001,
+    );
+
+    assert!(set.len() == 3);
+    assert!(merged == 4);
+    assert!(set.count(1.0) == 4);
+}
+
+#[test]
+fn it_answers_the_same_queries_once_frozen() {
+    let mut set = FloatHashSet::new(0.01);
+    set.extend((0..200u8).map(|i| f32::from(i) * 0.37 - 30.0));
+    set.extend([f32::INFINITY, -0.0, f32::NAN]);
+    let frozen = set.clone().freeze();
+
+    assert!(frozen.len() == set.len());
+    assert!(frozen.iter().take(3).eq([-30.0, -29.63, -29.26]));
+    let queries = (0..4000i16).map(|i| f32::from(i) * 0.0191 - 35.0);
+    for query in queries.chain([f32::INFINITY, f32::NEG_INFINITY, 0.004, -f32::NAN]) {
+        assert!(frozen.contains(query) == set.contains(query));
+    }
+}
+
+#[test]
+fn it_dedups_frozen_sets_of_near_equal_values() {
+    use std::collections::BTreeSet;
+
+    let frozen = |values: &[f32]| {
+        let mut set = FloatHashSet::new(0.00001);
+        set.extend(values.iter().copied());
+        set.freeze()
+    };
+    let shapes = [
+        frozen(&[0.0, 1.0, 2.0]),
+        frozen(&[2.000002, 1.000003, -0.0]),
+        frozen(&[0.0, 1.0, 2.0, f32::NAN]),
+        frozen(&[0.0, 1.0]),
+        frozen(&[0.0, 1.0, 2.5]),
+        frozen(&[0.0, 1.000001, 2.5, 2.500004]),
+        frozen(&[f32::NAN, 2.0, 1.0, 0.0]),
+    ];
+    let unique: BTreeSet<FrozenFloatSet> = shapes.iter().cloned().collect();
+
+    assert!(unique.len() == 4);
+    assert!(shapes[0] == shapes[1] && shapes[2] == shapes[6] && shapes[4] == shapes[5]);
+    assert!(unique.iter().map(FrozenFloatSet::len).eq([2, 3, 4, 3]));
+    assert!(shapes[0] < shapes[4] && shapes[3] < shapes[0] && shapes[0] < shapes[2]);
+    let coarser =
+        FloatHashSet::from_iter_with([0.0, 1.0, 2.0], 0.1, RepresentativePolicy::KeepFirst);
+    assert!(coarser.freeze() != shapes[0]);
+}
+
+#[test]
+fn it_finds_the_nearest_representative() {
+    let mut set = FloatHashSet::new(0.01);
+    set.extend([1.0, 2.0, 10.0, f32::NAN]);
+
+    assert!(set.nearest(1.4) == Some(1.0));
+    assert!(set.nearest(1.6) == Some(2.0));
+    assert!(set.nearest(2.004) == Some(2.0));
+    assert!(set.nearest(-1000.0) == Some(1.0));
+    assert!(set.nearest(f32::NAN).is_some_and(f32::is_nan));
+    assert!(FloatHashSet::new(0.01).nearest(1.0).is_none());
+}
+
+#[test]
+fn it_counts_the_representatives_retain_drops() {
+    let mut set = FloatHashSet::new(0.001);
+    set.extend([-2.0, -1.0, 0.0, 1.0, 1.0004, 2.0, 3.0]);
+
+    assert!(set.retain_count(|val| val >= 0.0) == 2);
+    assert!(set.len() == 4);
+    assert!(set.retain_count(|_| true) == 0);
+    assert!(!set.contains(-1.0) && set.contains(1.0004));
+}
+
+#[test]
+fn it_round_trips_through_canonical_bits() {
+    let mut set = FloatHashSet::new(0.001);
+    set.extend((0..300u16).map(|i| f32::from(i) * 0.0007 - 0.1));
+    set.extend([f32::NAN, f32::INFINITY, -0.0]);
+
+    let bits = set.to_canonical_bits();
+    let rebuilt = FloatHashSet::from_canonical_bits(&bits, 0.001);
+
+    assert!(rebuilt.len() == set.len());
+    assert!(rebuilt.to_canonical_bits() == bits);
+    let queries = (0..1000i16).map(|i| f32::from(i) * 0.00023 - 0.11);
+    assert!(queries
+        .into_iter()
+        .all(|query| rebuilt.contains(query) == set.contains(query)));
+}
+
+#[test]
+fn it_builds_with_capacity_and_a_custom_hasher() {
+    let mut set = FloatHashSet::with_capacity_and_hasher(0.01, 64, FixedState::with_seed(7));
+    let reserved = set.memory_footprint();
+    set.extend((0..64u8).map(|i| f32::from(i) * 0.5));
+    set.insert(3.004);
+
+    assert!(set.len() == 64);
+    assert!(set.contains(31.5) && !set.contains(32.0));
+    assert!(set.count(3.0) == 2);
+    assert!(reserved > FloatHashSet::new(0.01).memory_footprint());
+}
+
+#[test]
+fn it_sorts_a_diff_into_exact_near_and_disjoint() {
+    let mut ours = FloatHashSet::new(0.01);
+    ours.extend([1.0, 2.0, 3.0, 5.0, 8.0]);
+    let mut theirs = FloatHashSet::new(0.01);
+    theirs.extend([1.0, 2.004, 3.0, 4.0, 7.996, 9.0]);
+
+    let diff = ours.tolerant_diff(&theirs);
+
+    assert!(diff.only_in_self == vec![5.0]);
+    assert!(diff.only_in_other == vec![4.0, 9.0]);
+    assert!(diff.near_matches == vec![(2.0, 2.004), (8.0, 7.996)]);
+}
+
+#[test]
+fn it_validates_a_well_built_set_and_catches_a_corrupted_one() {
+    let mut set = FloatHashSet::new(0.01);
+    set.extend([1.0, 1.004, 2.0, 2.5, -0.0, 0.003, f32::NAN, f32::NAN]);
+    set.remove(2.5);
+    assert!(set.validate().is_ok());
+
+    set.map.insert_unchecked(1.005, 1);
+
+    assert!(matches!(
+        set.validate(),
+        Err(IntegrityError::WithinTolerance(a, b)) if [a, b] == [1.0, 1.005] || [a, b] == [1.005, 1.0]
+    ));
+}
+
+#[test]
+fn it_reads_raw_little_endian_floats() {
+    /// Hands out at most 3 bytes per read, so values straddle reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    let bytes: Vec<u8> = [1.0f32, 1.004, 2.5, -7.25, f32::NAN]
+        .iter()
+        .flat_map(|val| val.to_le_bytes())
+        .collect();
+
+    let set = FloatHashSet::from_reader(Trickle(&bytes), 0.01).unwrap();
+    assert!(set.len() == 4);
+    assert!(set.contains(1.002) && set.contains(2.5) && set.contains(-7.25));
+    assert!(set.contains(f32::NAN) && !set.contains(7.25));
+
+    let err = FloatHashSet::from_reader(&bytes[..bytes.len() - 1], 0.01).unwrap_err();
+    assert!(err.kind() == io::ErrorKind::UnexpectedEof);
+    assert!(FloatHashSet::from_reader(io::empty(), 0.01)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn it_keeps_huge_values_apart() {
+    let mut set = FloatHashSet::new(0.00001);
+    set.extend([f32::MAX, f32::MAX.next_down(), f32::INFINITY, f32::MAX]);
+
+    assert!(set.len() == 3);
+    assert!(set.get(f32::MAX.next_down()) == Some(f32::MAX.next_down()));
+}
+
+#[test]
+fn it_round_trips_raw_floats() {
+    let mut set = FloatHashSet::new(0.00001);
+    set.extend(vec![3.0, 1.0, 3.000004, 2.0, 0.999996]);
+
+    let mut borrowed: Vec<f32> = set.iter().collect();
+    borrowed.sort_by(f32::total_cmp);
+    let mut owned: Vec<f32> = set.into_iter().collect();
+    owned.sort_by(f32::total_cmp);
+
+    assert!(borrowed == vec![1.0, 2.0, 3.0]);
+    assert!(owned == borrowed);
+}
+
+#[test]
+fn it_iterates_identically_with_the_same_seed() {
+    let values = (0..100u8).map(|i| f32::from(i) * 0.37);
+    let mut set_1 = FloatHashSet::with_fixed_seed(0.001, 42);
+    set_1.extend(values.clone());
+    let mut set_2 = FloatHashSet::with_fixed_seed(0.001, 42);
+    set_2.extend(values);
+
+    assert!(set_1.iter().collect::<Vec<_>>() == set_2.iter().collect::<Vec<_>>());
+}
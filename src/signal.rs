@@ -0,0 +1,170 @@
+#[cfg(test)]
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use crate::grid;
+use crate::mode::validated_tolerance;
+use crate::ToleranceError;
+
+/// A sequence of samples (e.g. an audio frame) that hashes and compares
+/// tolerantly, sample by sample.
+///
+/// Two signals are equal iff they have the same tolerance, the same length,
+/// and every pair of samples snaps to the same grid cell, which is exactly
+/// what's hashed (after a length prefix, so signals of different lengths
+/// don't collide just because one is a prefix of the other). As with
+/// [`crate::F32Wrapper`], NaNs match each other, and samples within
+/// tolerance but either side of a cell edge don't; see
+/// [`SignalWrapper::approx_eq`] for that.
+#[derive(Debug, Clone)]
+pub struct SignalWrapper {
+    samples: Vec<f32>,
+    tolerance: f32,
+}
+
+impl SignalWrapper {
+    /// `tolerance` must be finite and not negative, which debug builds
+    /// assert.
+    pub fn new(samples: Vec<f32>, tolerance: f32) -> Self {
+        debug_assert!(
+            validated_tolerance(tolerance).is_ok(),
+            "invalid tolerance {tolerance}: must be finite and at least zero"
+        );
+        SignalWrapper { samples, tolerance }
+    }
+
+    /// Like [`SignalWrapper::new`], but returns an error for a negative,
+    /// NaN, or infinite tolerance in every build.
+    pub fn try_new(samples: Vec<f32>, tolerance: f32) -> Result<Self, ToleranceError> {
+        Ok(Self::new(samples, validated_tolerance(tolerance)?))
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Whether the signals have the same length and every pair of samples is
+    /// within tolerance, using the wider of the two tolerances. Unlike `==`
+    /// this isn't transitive, so it's no use for hashing.
+    pub fn approx_eq(&self, other: &Self) -> bool {
+        let tolerance = self.tolerance.max(other.tolerance);
+        self.samples.len() == other.samples.len()
+            && self
+                .samples
+                .iter()
+                .zip(&other.samples)
+                .all(|(a, b)| a.to_bits() == b.to_bits() || (a - b).abs() <= tolerance)
+    }
+}
+
+impl PartialEq for SignalWrapper {
+    fn eq(&self, other: &Self) -> bool {
+        self.tolerance == other.tolerance
+            && self.samples.len() == other.samples.len()
+            && self.samples.iter().zip(&other.samples).all(|(a, b)| {
+                grid::cell_or_nan(*a, self.tolerance) == grid::cell_or_nan(*b, self.tolerance)
+            })
+    }
+}
+
+impl Eq for SignalWrapper {}
+
+impl Hash for SignalWrapper {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.samples.len());
+        for sample in &self.samples {
+            grid::cell_or_nan(*sample, self.tolerance).hash(state);
+        }
+    }
+}
+
+#[test]
+fn it_treats_perturbed_signals_as_the_same() {
+    let signal = vec![0.1, 0.5, -0.25, 1.0, 0.0];
+    let perturbed = signal
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            if i % 2 == 0 {
+                sample + 2e-6
+            } else {
+                sample - 2e-6
+            }
+        })
+        .collect();
+
+    let mut set = HashSet::new();
+    set.insert(SignalWrapper::new(signal, 0.0001));
+    set.insert(SignalWrapper::new(perturbed, 0.0001));
+
+    assert!(set.len() == 1);
+}
+
+#[test]
+fn it_treats_signals_of_different_lengths_as_different() {
+    let mut set = HashSet::new();
+    set.insert(SignalWrapper::new(vec![0.1, 0.5], 0.0001));
+    set.insert(SignalWrapper::new(vec![0.1, 0.5, 0.0], 0.0001));
+
+    assert!(set.len() == 2);
+}
+
+#[test]
+fn it_treats_signals_with_one_distant_sample_as_different() {
+    let a = SignalWrapper::new(vec![0.1, 0.5, -0.25], 0.0001);
+    let b = SignalWrapper::new(vec![0.1, 0.5, -0.2], 0.0001);
+
+    assert!(a != b);
+}
+
+#[test]
+fn it_hashes_equal_signals_alike_even_across_cell_edges() {
+    use std::collections::hash_map::DefaultHasher;
+
+    let hash_of = |signal: &SignalWrapper| {
+        let mut hasher = DefaultHasher::new();
+        signal.hash(&mut hasher);
+        hasher.finish()
+    };
+    let straddling = (
+        SignalWrapper::new(vec![0.000149], 0.0001),
+        SignalWrapper::new(vec![0.000151], 0.0001),
+    );
+    assert!(straddling.0 != straddling.1);
+    assert!(straddling.0.approx_eq(&straddling.1));
+
+    let signals = [
+        straddling.0,
+        straddling.1,
+        SignalWrapper::new(vec![0.00012], 0.0001),
+        SignalWrapper::new(vec![0.00012], 0.001),
+        SignalWrapper::new(vec![f32::NAN], 0.0001),
+        SignalWrapper::new(vec![-f32::NAN], 0.0001),
+        SignalWrapper::new(vec![0.0], 0.0001),
+        SignalWrapper::new(vec![-0.0], 0.0001),
+    ];
+    for a in &signals {
+        for b in &signals {
+            assert!(a != b || hash_of(a) == hash_of(b), "{a:?} vs {b:?}");
+        }
+    }
+    assert!(signals[0] == signals[2] && signals[2] != signals[3]);
+    assert!(signals[4] == signals[5] && signals[6] == signals[7]);
+}
+
+#[test]
+fn it_rejects_negative_and_nan_tolerances() {
+    assert!(SignalWrapper::try_new(vec![1.0], -0.1).is_err());
+    assert!(SignalWrapper::try_new(vec![1.0], f32::NAN).is_err());
+    assert!(SignalWrapper::try_new(vec![1.0], f32::INFINITY).is_err());
+    assert!(
+        SignalWrapper::try_new(vec![1.0], 0.001)
+            .unwrap()
+            .tolerance()
+            == 0.001
+    );
+}
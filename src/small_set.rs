@@ -0,0 +1,174 @@
+use crate::mode::validated_tolerance;
+use crate::{FloatHashSet, ToleranceError};
+
+/// Sets up to this size are scanned linearly instead of hashed.
+const INLINE_CAPACITY: usize = 64;
+
+/// A [`FloatHashSet`] for sets that usually stay tiny.
+///
+/// Up to 64 representatives are kept in a plain `Vec` and compared one by
+/// one. Building a set and probing it is several times faster than with a
+/// `FloatHashSet` at a handful of values, still ahead at 63, and falls
+/// behind somewhere before 95, per `benches/small_set.rs`. Past 64 it moves
+/// everything into a `FloatHashSet` for good. Either way it keeps the
+/// same representatives and answers lookups the same as a `FloatHashSet`.
+#[derive(Debug, Clone)]
+pub struct SmallFloatSet {
+    tolerance: f32,
+    repr: Repr,
+}
+
+#[derive(Debug, Clone)]
+enum Repr {
+    Inline(Vec<f32>),
+    Hashed(FloatHashSet),
+}
+
+impl SmallFloatSet {
+    /// Creates an empty set. `tolerance` must be finite and not negative,
+    /// which debug builds assert.
+    pub fn new(tolerance: f32) -> Self {
+        debug_assert!(
+            validated_tolerance(tolerance).is_ok(),
+            "invalid tolerance {tolerance}: must be finite and at least zero"
+        );
+        SmallFloatSet {
+            tolerance,
+            repr: Repr::Inline(Vec::new()),
+        }
+    }
+
+    /// Like [`SmallFloatSet::new`], but returns an error for a negative,
+    /// NaN, or infinite tolerance in every build.
+    pub fn try_new(tolerance: f32) -> Result<Self, ToleranceError> {
+        Ok(Self::new(validated_tolerance(tolerance)?))
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Number of clusters.
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(values) => values.len(),
+            Repr::Hashed(set) => set.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `value` as a new cluster. Returns `false` if it was within
+    /// tolerance of an existing representative, which is left unchanged.
+    pub fn insert(&mut self, value: f32) -> bool {
+        let values = match &mut self.repr {
+            Repr::Hashed(set) => return set.insert(value),
+            Repr::Inline(values) => values,
+        };
+        if Self::closest(values, value, self.tolerance).is_some() {
+            return false;
+        }
+        if values.len() < INLINE_CAPACITY {
+            values.push(value);
+        } else {
+            let mut set = FloatHashSet::new(self.tolerance);
+            set.extend(values.drain(..));
+            set.insert(value);
+            self.repr = Repr::Hashed(set);
+        }
+        true
+    }
+
+    pub fn contains(&self, value: f32) -> bool {
+        self.get(value).is_some()
+    }
+
+    /// The representative within tolerance of `value`, if any.
+    pub fn get(&self, value: f32) -> Option<f32> {
+        match &self.repr {
+            Repr::Inline(values) => {
+                Self::closest(values, value, self.tolerance).map(|idx| values[idx])
+            }
+            Repr::Hashed(set) => set.get(value),
+        }
+    }
+
+    pub fn remove(&mut self, value: f32) -> bool {
+        match &mut self.repr {
+            Repr::Inline(values) => match Self::closest(values, value, self.tolerance) {
+                Some(idx) => {
+                    values.swap_remove(idx);
+                    true
+                }
+                None => false,
+            },
+            Repr::Hashed(set) => set.remove(value),
+        }
+    }
+
+    /// The representatives, in arbitrary order.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = f32> + '_> {
+        match &self.repr {
+            Repr::Inline(values) => Box::new(values.iter().copied()),
+            Repr::Hashed(set) => Box::new(set.iter()),
+        }
+    }
+
+    /// Index of the stored value closest to `value` among those within
+    /// tolerance, measured in `f64` and lumping NaNs together like
+    /// [`crate::ApproxHashMap`] does.
+    fn closest(values: &[f32], value: f32, tolerance: f32) -> Option<usize> {
+        let mut best: Option<(usize, f64)> = None;
+        for (idx, stored) in values.iter().enumerate() {
+            let diff = if stored.to_bits() == value.to_bits() || (stored.is_nan() && value.is_nan())
+            {
+                0.0
+            } else {
+                (f64::from(*stored) - f64::from(value)).abs()
+            };
+            if diff <= f64::from(tolerance) && best.is_none_or(|(_, best_diff)| diff < best_diff) {
+                best = Some((idx, diff));
+            }
+        }
+        best.map(|(idx, _)| idx)
+    }
+}
+
+impl Extend<f32> for SmallFloatSet {
+    fn extend<I: IntoIterator<Item = f32>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+#[test]
+fn it_matches_float_hash_set_on_either_side_of_promotion() {
+    let values: Vec<f32> = (0..200u8)
+        .map(|i| f32::from(i % 90) * 0.3 + f32::from(i % 3) * 0.04)
+        .collect();
+    for len in [5, INLINE_CAPACITY, INLINE_CAPACITY + 1, 200] {
+        let mut small = SmallFloatSet::new(0.05);
+        let mut hashed = FloatHashSet::new(0.05);
+        for val in &values[..len] {
+            assert!(small.insert(*val) == hashed.insert(*val));
+        }
+
+        assert!(small.len() == hashed.len());
+        for probe in (0..400u16).map(|i| f32::from(i) * 0.081) {
+            assert!(small.get(probe) == hashed.get(probe));
+        }
+        assert!(small.remove(0.31) == hashed.remove(0.31));
+        assert!(small.len() == hashed.len());
+    }
+}
+
+#[test]
+fn it_rejects_negative_and_nan_tolerances() {
+    assert!(SmallFloatSet::try_new(-0.1).is_err());
+    assert!(SmallFloatSet::try_new(f32::NAN).is_err());
+    assert!(SmallFloatSet::try_new(0.0).is_ok());
+    assert!(SmallFloatSet::try_new(0.001).unwrap().tolerance() == 0.001);
+}
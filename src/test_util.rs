@@ -0,0 +1,54 @@
+//! Helpers for checking this crate's guarantees against your own data, from
+//! your own tests. Enable the `test-util` feature to use them outside the
+//! crate.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::F32Wrapper;
+
+/// Checks the `Hash` contract on every pair of `values` wrapped at
+/// `tolerance`: wrappers that compare equal must hash equal.
+///
+/// # Panics
+///
+/// On the first pair that compares equal but hashes differently, naming the
+/// pair and the hashes.
+pub fn assert_hash_eq_consistent(values: &[f32], tolerance: f32) {
+    let wrapped: Vec<(F32Wrapper, u64)> = values
+        .iter()
+        .map(|val| {
+            let val = F32Wrapper::with_tolerance(*val, tolerance);
+            let mut hasher = DefaultHasher::new();
+            val.hash(&mut hasher);
+            (val, hasher.finish())
+        })
+        .collect();
+
+    for (idx, (a, a_hash)) in wrapped.iter().enumerate() {
+        for (b, b_hash) in &wrapped[idx + 1..] {
+            assert!(
+                a != b || a_hash == b_hash,
+                "{} and {} are equal at tolerance {tolerance} but hash differently \
+                 ({a_hash:#018x} vs {b_hash:#018x})",
+                a.value(),
+                b.value(),
+            );
+        }
+    }
+}
+
+#[test]
+fn it_accepts_well_separated_values() {
+    let values: Vec<f32> = (0..200u8)
+        .flat_map(|i| [f32::from(i), f32::from(i) + 0.000001])
+        .collect();
+
+    assert_hash_eq_consistent(&values, 0.00001);
+}
+
+#[test]
+fn it_accepts_values_straddling_cell_edges() {
+    // Within tolerance but in neighbouring cells, so they're unequal too
+    assert_hash_eq_consistent(&[0.49, 0.5, 0.51, 1.49, 1.5], 1.0);
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use crate::grid;
+
+/// Welds vertices within `tolerance` of each other (on every axis) into one,
+/// returning the unique vertices and, for each input vertex, the index of the
+/// unique vertex it became, ready to use as an index buffer.
+///
+/// Vertices are filed into a 3D grid of `tolerance`-wide cells and each one
+/// probes every cell a match could be in, so coincident vertices merge even
+/// when they straddle a cell edge. The first vertex of each cluster is the one
+/// kept.
+pub fn weld_vertices(vertices: &[[f32; 3]], tolerance: f32) -> (Vec<[f32; 3]>, Vec<u32>) {
+    let tolerance_f64 = f64::from(tolerance);
+    let cell_range = |component: f32| {
+        let component = f64::from(component);
+        grid::cell(component - tolerance_f64, tolerance_f64)
+            ..=grid::cell(component + tolerance_f64, tolerance_f64)
+    };
+    let close =
+        |a: &[f32; 3], b: &[f32; 3]| a.iter().zip(b).all(|(a, b)| (a - b).abs() <= tolerance);
+
+    let mut cells: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let mut unique: Vec<[f32; 3]> = Vec::new();
+    let mut remap = Vec::with_capacity(vertices.len());
+
+    for vertex in vertices {
+        let mut found = None;
+        'probe: for x in cell_range(vertex[0]) {
+            for y in cell_range(vertex[1]) {
+                for z in cell_range(vertex[2]) {
+                    let Some(candidates) = cells.get(&[x, y, z]) else {
+                        continue;
+                    };
+                    if let Some(idx) = candidates
+                        .iter()
+                        .find(|idx| close(&unique[**idx as usize], vertex))
+                    {
+                        found = Some(*idx);
+                        break 'probe;
+                    }
+                }
+            }
+        }
+
+        let idx = found.unwrap_or_else(|| {
+            let idx = u32::try_from(unique.len()).expect("more than u32::MAX unique vertices");
+            let home = vertex.map(|component| grid::cell(f64::from(component), tolerance_f64));
+            cells.entry(home).or_default().push(idx);
+            unique.push(*vertex);
+            idx
+        });
+        remap.push(idx);
+    }
+
+    (unique, remap)
+}
+
+#[test]
+fn it_welds_a_cube_down_to_its_corners() {
+    // Offset so the corners sit right on cell edges, then jittered so
+    // copies of a corner straddle them
+    let offset = 0.0005;
+    let mut vertices = Vec::new();
+    for face in 0..6 {
+        for corner in 0..4u8 {
+            let axis = face % 3;
+            let side = f32::from(u8::from(face >= 3));
+            let (u, v) = (f32::from(corner & 1), f32::from(corner >> 1));
+            let mut vertex = [0.0; 3];
+            vertex[axis] = side;
+            vertex[(axis + 1) % 3] = u;
+            vertex[(axis + 2) % 3] = v;
+            #[allow(clippy::cast_precision_loss)]
+            let jitter = ((vertices.len() % 3) as f32 - 1.0) * 0.0001;
+            vertices.push(vertex.map(|component| component + offset + jitter));
+        }
+    }
+
+    let (unique, remap) = weld_vertices(&vertices, 0.001);
+
+    assert!(unique.len() == 8);
+    assert!(remap.len() == 24);
+    for (vertex, idx) in vertices.iter().zip(&remap) {
+        let welded = unique[*idx as usize];
+        assert!(vertex
+            .iter()
+            .zip(welded)
+            .all(|(a, b)| (a - b).abs() <= 0.001));
+    }
+}
@@ -0,0 +1,1467 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+#[cfg(test)]
+use std::collections::hash_map::DefaultHasher;
+#[cfg(test)]
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::ParseFloatError;
+
+use crate::fnv::FnvHasher;
+use crate::{grid, Canonicalizer, ComparisonMode, ToleranceError};
+#[cfg(test)]
+use crate::{snap_to_grid, Rounding};
+
+pub const F32_ERROR_TOLERANCE: f32 = 0.00001;
+
+thread_local! {
+    static DEFAULT_TOLERANCE: Cell<f32> = const { Cell::new(F32_ERROR_TOLERANCE) };
+}
+
+/// Sets the tolerance [`F32Wrapper::new`] (and the other constructors that
+/// don't take one) uses, for the current thread only.
+///
+/// The default is thread-local so that setting it can't race with, or
+/// silently change, wrappers being built on other threads. Every thread
+/// starts out at [`F32_ERROR_TOLERANCE`], so set it again on any worker
+/// threads that should share it. Wrappers keep the tolerance they were built
+/// with; changing the default doesn't touch existing ones.
+///
+/// Debug builds assert that `tolerance` is finite and not negative.
+pub fn set_default_tolerance(tolerance: f32) {
+    debug_assert!(
+        tolerance.is_finite() && tolerance >= 0.0,
+        "invalid tolerance {tolerance}: must be finite and at least zero"
+    );
+    DEFAULT_TOLERANCE.with(|default| default.set(tolerance));
+}
+
+/// The current thread's default tolerance; see [`set_default_tolerance`].
+pub fn default_tolerance() -> f32 {
+    DEFAULT_TOLERANCE.with(Cell::get)
+}
+
+const F32_BITS: usize = 32;
+const F32_EXPONENT_BITS: usize = 8;
+const F32_MANTISA_BITS: usize = 23;
+
+#[derive(Copy, Clone)]
+pub struct F32Wrapper {
+    inner: f32,
+    mode: ComparisonMode,
+    distinct_zeros: bool,
+    ignore_sign: bool,
+}
+
+/// Two wrappers are equal when they snap to the same grid cell, i.e. exactly
+/// when they hash alike. That makes this a real equivalence relation, so
+/// `F32Wrapper` is sound as a `HashSet` or `HashMap` key, at the price of
+/// values within tolerance of each other but on either side of a cell edge
+/// comparing unequal. For "within tolerance" comparisons use [`ApproxF32`]
+/// (or compare against a raw `f32`), and for collections that merge every
+/// value within tolerance use [`crate::FloatHashSet`].
+///
+/// All NaNs are equal to each other and to nothing else. With distinct zeros
+/// the sign has to match too, and since their negative values hash
+/// differently, a wrapper with distinct zeros never equals one without.
+/// Wrappers that ignore sign are compared by magnitude, and likewise never
+/// equal ones that don't: `3.0` ignoring sign would otherwise equal both
+/// `3.0` and `-3.0`, which don't equal each other.
+///
+/// Wrappers with different modes (including the same kind of mode at
+/// different tolerances) have no cells in common to compare, and hash by
+/// different ones, so they're never equal, even with identical bits. Keep a
+/// collection to a single mode.
+///
+/// [`ApproxF32`]: crate::ApproxF32
+impl PartialEq for F32Wrapper {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        if self.mode != other.mode
+            || self.distinct_zeros != other.distinct_zeros
+            || self.ignore_sign != other.ignore_sign
+        {
+            return false;
+        }
+        if self.to_bits() == other.to_bits() {
+            return true;
+        }
+        match (self.inner.is_nan(), other.inner.is_nan()) {
+            (true, true) => return true,
+            (true, false) | (false, true) => return false,
+            (false, false) => {}
+        }
+        if self.distinct_zeros && self.sign_bit() != other.sign_bit() {
+            return false;
+        }
+        self.mode.cell(self.compared_value()) == self.mode.cell(other.compared_value())
+    }
+}
+
+impl Eq for F32Wrapper {}
+
+/// Shows the value alongside the mode it's compared under, e.g.
+/// `F32Wrapper { value: 3.14, mode: Relative(1e-6) }`. Distinct zeros and
+/// ignored signs are only mentioned when enabled.
+impl fmt::Debug for F32Wrapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("F32Wrapper");
+        debug.field("value", &self.inner).field("mode", &self.mode);
+        if self.distinct_zeros {
+            debug.field("distinct_zeros", &true);
+        }
+        if self.ignore_sign {
+            debug.field("ignore_sign", &true);
+        }
+        debug.finish()
+    }
+}
+
+/// Compares against a raw float under the wrapper's own mode.
+impl PartialEq<f32> for F32Wrapper {
+    fn eq(&self, other: &f32) -> bool {
+        if self.to_bits() == other.to_bits() {
+            return true;
+        }
+        if self.distinct_zeros && self.inner == 0.0 && *other == 0.0 {
+            return false;
+        }
+        if self.ignore_sign {
+            return self.mode.matches(self.inner.abs(), other.abs());
+        }
+        self.mode.matches(self.inner, *other)
+    }
+}
+
+/// Orders a wrapper against a raw threshold, treating anything within
+/// tolerance as `Equal`, so `wrapper < 10.0` only holds if the value is
+/// clearly below 10.
+///
+/// Like equality this isn't transitive: `a < t` and `b == t` say nothing
+/// about how `a` and `b` compare, and a value can be `Equal` to two
+/// thresholds that are themselves ordered.
+impl PartialOrd<f32> for F32Wrapper {
+    fn partial_cmp(&self, other: &f32) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        if self.ignore_sign {
+            return self.inner.abs().partial_cmp(&other.abs());
+        }
+        self.inner.partial_cmp(other)
+    }
+}
+
+impl F32Wrapper {
+    /// Wraps `val` with the current thread's [`default_tolerance`], which is
+    /// `F32_ERROR_TOLERANCE` unless changed.
+    #[inline]
+    pub fn new(val: f32) -> Self {
+        Self::with_tolerance(val, default_tolerance())
+    }
+
+    /// Wraps `val` with its own tolerance instead of `F32_ERROR_TOLERANCE`.
+    /// Wrappers with different tolerances are never equal and hash apart,
+    /// so keep a collection to a single tolerance.
+    ///
+    /// A tolerance of `0.0` means exact comparison: only bit-identical values
+    /// (and the two zeros, unless kept distinct) are equal. It must be finite
+    /// and not negative, which debug builds assert; see
+    /// [`F32Wrapper::try_with_tolerance`] for tolerances that aren't known to
+    /// be.
+    #[inline]
+    pub fn with_tolerance(val: f32, tolerance: f32) -> Self {
+        Self::with_mode(val, ComparisonMode::Absolute(tolerance))
+    }
+
+    /// Like [`F32Wrapper::with_tolerance`], but returns an error for a
+    /// negative, NaN, or infinite tolerance in every build.
+    pub fn try_with_tolerance(val: f32, tolerance: f32) -> Result<Self, ToleranceError> {
+        Self::try_with_mode(val, ComparisonMode::Absolute(tolerance))
+    }
+
+    /// Debug builds assert that `mode` passes [`ComparisonMode::validated`].
+    #[inline]
+    pub fn with_mode(val: f32, mode: ComparisonMode) -> Self {
+        debug_assert!(
+            mode.validated().is_ok(),
+            "invalid tolerance in {mode:?}: must be finite and at least zero"
+        );
+        F32Wrapper {
+            inner: val,
+            mode,
+            distinct_zeros: false,
+            ignore_sign: false,
+        }
+    }
+
+    /// Like [`F32Wrapper::with_mode`], but returns an error for an invalid
+    /// tolerance in every build.
+    pub fn try_with_mode(val: f32, mode: ComparisonMode) -> Result<Self, ToleranceError> {
+        Ok(Self::with_mode(val, mode.validated()?))
+    }
+
+    /// Snaps to a grid of `1e-3`, e.g. millimetres when working in metres.
+    pub fn with_milli(val: f32) -> Self {
+        Self::with_tolerance(val, 1e-3)
+    }
+
+    /// Snaps to a grid of `1e-6`.
+    pub fn with_micro(val: f32) -> Self {
+        Self::with_tolerance(val, 1e-6)
+    }
+
+    /// Snaps to a grid of `1e-9`.
+    pub fn with_nano(val: f32) -> Self {
+        Self::with_tolerance(val, 1e-9)
+    }
+
+    /// Parses `s` with a tolerance of half its last decimal place, taking
+    /// the digits written as the precision the value is known to. So `"1.2"`
+    /// gets `0.05` but `"1.20"` gets `0.005`, and `"12e3"` gets `500`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    pub fn from_str_inferred_tolerance(s: &str) -> Result<Self, ParseFloatError> {
+        let s = s.trim();
+        let val: f32 = s.parse()?;
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            // The whole string parsed, so the exponent is a valid integer
+            Some(idx) => (&s[..idx], s[idx + 1..].parse::<i32>().unwrap_or(0)),
+            None => (s, 0),
+        };
+        let decimals = mantissa
+            .find('.')
+            .map_or(0, |idx| (mantissa.len() - idx - 1) as i32);
+        let tolerance = 0.5 * 10f64.powi(exponent.saturating_sub(decimals));
+        // Past `f32::MAX` the value itself is infinite, and infinities only
+        // ever match themselves anyway
+        Ok(Self::with_tolerance(val, (tolerance as f32).min(f32::MAX)))
+    }
+
+    /// Equal to values within `tolerance` times the larger magnitude of the
+    /// two. See [`ComparisonMode::Relative`] for how zero is handled.
+    pub fn with_relative(val: f32, tolerance: f32) -> Self {
+        Self::with_mode(val, ComparisonMode::Relative(tolerance))
+    }
+
+    /// Equal to values within `percent` percent of each other, i.e. relative
+    /// mode at a tolerance of `percent / 100`.
+    pub fn with_percent(val: f32, percent: f32) -> Self {
+        Self::with_relative(val, percent / 100.0)
+    }
+
+    /// An absolute tolerance of `ulps` times the gap between `val` and the
+    /// next float up, so it scales with the value's magnitude much like
+    /// relative mode but compares by plain distance. The tolerance is fixed
+    /// when wrapping: it doesn't follow whatever `val` is compared against.
+    ///
+    /// NaNs and infinities have no gap to a next float and get a tolerance of
+    /// zero; see [`F32Wrapper::with_tolerance`].
+    pub fn auto_tolerance(val: f32, ulps: u32) -> Self {
+        let tolerance = ComparisonMode::Ulps(ulps).tolerance_at(val);
+        Self::with_tolerance(
+            val,
+            if tolerance.is_finite() {
+                tolerance
+            } else {
+                0.0
+            },
+        )
+    }
+
+    /// Equal to exactly the values with the same sign and exponent bits,
+    /// i.e. in the same power-of-two bucket; see
+    /// [`ComparisonMode::Magnitude`].
+    pub fn with_magnitude_buckets(val: f32) -> Self {
+        Self::with_mode(val, ComparisonMode::Magnitude)
+    }
+
+    /// Equal to exactly the values whose magnitudes are in the same
+    /// `(1 + ratio)`-wide logarithmic bucket, so `ratio = 0.01` buckets within
+    /// roughly 1% of each other at any scale; see
+    /// [`ComparisonMode::LogBuckets`].
+    pub fn with_log_buckets(val: f32, ratio: f32) -> Self {
+        Self::with_mode(val, ComparisonMode::LogBuckets(ratio))
+    }
+
+    /// Like [`F32Wrapper::new`], but keeps `0.0` and `-0.0` apart instead of
+    /// merging them, for math that cares which side of a branch cut it's on.
+    /// The sign bit then takes part in equality and hashing even for zero.
+    ///
+    /// Don't mix these with merged-zero wrappers in one collection; a `-0.0`
+    /// of each kind hash differently.
+    pub fn with_distinct_zeros(val: f32) -> Self {
+        F32Wrapper {
+            distinct_zeros: true,
+            ..Self::new(val)
+        }
+    }
+
+    /// Like [`F32Wrapper::new`], but compares and hashes by magnitude alone,
+    /// so a value and its negation are the same, e.g. for amplitudes whose
+    /// phase doesn't matter. Orderings against raw floats compare
+    /// magnitudes too.
+    ///
+    /// Same as with distinct zeros, don't mix these with wrappers that keep
+    /// the sign in one collection; `-3.0` of each kind hash differently.
+    pub fn with_sign_ignored(val: f32) -> Self {
+        F32Wrapper {
+            ignore_sign: true,
+            ..Self::new(val)
+        }
+    }
+
+    #[inline]
+    pub fn value(self) -> f32 {
+        self.inner
+    }
+
+    #[inline]
+    pub fn mode(self) -> ComparisonMode {
+        self.mode
+    }
+
+    /// The value in fixed point with `scale` decimal places, i.e.
+    /// `round(value * 10^scale)`, for use as a plain integer key. Values
+    /// within half of `10^-scale` of the same multiple share an integer.
+    ///
+    /// Returns `None` for NaN and infinities and when the result wouldn't
+    /// fit in an `i64`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    pub fn to_fixed_point(&self, scale: u32) -> Option<i64> {
+        // 2^63, the first magnitude past i64::MAX
+        const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+        let factor = 10f64.powi(i32::try_from(scale).ok()?);
+        let scaled = (f64::from(self.inner) * factor).round();
+        if !scaled.is_finite() || !(-LIMIT..LIMIT).contains(&scaled) {
+            return None;
+        }
+        Some(scaled as i64)
+    }
+
+    /// The next float up from this one, keeping the same mode. Both zeros
+    /// step to the smallest positive subnormal, `f32::MAX` steps to infinity,
+    /// and infinity and NaN stay put.
+    pub fn next_representable_above(&self) -> Self {
+        F32Wrapper {
+            inner: self.inner.next_up(),
+            ..*self
+        }
+    }
+
+    /// The next float down from this one, mirroring
+    /// [`F32Wrapper::next_representable_above`].
+    pub fn next_representable_below(&self) -> Self {
+        F32Wrapper {
+            inner: self.inner.next_down(),
+            ..*self
+        }
+    }
+
+    /// The absolute tolerance around this value; in relative or ULP mode it
+    /// depends on the value's magnitude.
+    pub fn tolerance(self) -> f32 {
+        self.mode.tolerance_at(self.inner)
+    }
+
+    /// `self + rhs`, or `None` if that isn't finite. The result keeps
+    /// `self`'s mode.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.finite_result(self.inner + rhs.inner)
+    }
+
+    /// `self - rhs`, or `None` if that isn't finite.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.finite_result(self.inner - rhs.inner)
+    }
+
+    /// `self * rhs`, or `None` if that isn't finite.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.finite_result(self.inner * rhs.inner)
+    }
+
+    /// `self / rhs`, or `None` if that isn't finite, which includes dividing
+    /// by zero.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.finite_result(self.inner / rhs.inner)
+    }
+
+    fn finite_result(self, result: f32) -> Option<Self> {
+        result.is_finite().then_some(F32Wrapper {
+            inner: result,
+            ..self
+        })
+    }
+
+    /// The square root, with the tolerance carried through: to first order
+    /// an absolute error `δ` in `x` becomes `δ / (2√x)`.
+    ///
+    /// [`F32Wrapper::powi`] and [`F32Wrapper::powf`] propagate tolerance the
+    /// same way. Rather than trusting the derivative, which blows up at e.g.
+    /// `√0`, the new tolerance is how far the result moves when the inputs
+    /// move to the ends of their tolerance, which agrees with the first-order
+    /// formula for small tolerances. Absolute mode gets that as its new
+    /// tolerance and relative mode gets it relative to the result. ULP and
+    /// magnitude modes are about the representation rather than an error
+    /// bound, so they're kept as they are.
+    pub fn sqrt(self) -> Self {
+        self.propagate(f64::sqrt)
+    }
+
+    /// `self^n`. To first order the tolerance becomes `|n·x^(n-1)|·δ`; see
+    /// [`F32Wrapper::sqrt`].
+    pub fn powi(self, n: i32) -> Self {
+        self.propagate(|val| val.powi(n))
+    }
+
+    /// `self^exponent`, with both tolerances carried through. To first order
+    /// the tolerance becomes `|y·x^(y-1)|·δx + |x^y·ln x|·δy`; see
+    /// [`F32Wrapper::sqrt`]. The result keeps `self`'s mode.
+    pub fn powf(self, exponent: Self) -> Self {
+        let (base, exp) = (f64::from(self.inner), f64::from(exponent.inner));
+        let result = base.powf(exp);
+        let ends =
+            |val: f64, tolerance: f32| [val - f64::from(tolerance), val + f64::from(tolerance)];
+        let deviation = ends(base, self.tolerance())
+            .into_iter()
+            .flat_map(|base| ends(exp, exponent.tolerance()).map(|exp| base.powf(exp) - result))
+            .fold(0.0, |widest: f64, deviation| widest.max(deviation.abs()));
+        self.with_propagated(result, deviation)
+    }
+
+    /// Applies `f` to the value and works out the tolerance from how far
+    /// `f` moves across the ends of the value's band. Done in `f64`, so that
+    /// the tolerance isn't swamped by rounding in the result.
+    fn propagate<M: Fn(f64) -> f64>(self, f: M) -> Self {
+        let (val, tolerance) = (f64::from(self.inner), f64::from(self.tolerance()));
+        let result = f(val);
+        let deviation = [val - tolerance, val + tolerance]
+            .into_iter()
+            .fold(0.0, |widest: f64, end| widest.max((f(end) - result).abs()));
+        self.with_propagated(result, deviation)
+    }
+
+    /// Wraps `result` under `self`'s kind of mode, with `deviation` as the
+    /// new tolerance. `f64::max` skips NaN, so ends outside `f`'s domain
+    /// (like below 0 for `sqrt`) have already dropped out.
+    #[allow(clippy::cast_possible_truncation)]
+    fn with_propagated(self, result: f64, deviation: f64) -> Self {
+        // Past `f32::MAX` everything finite would match anyway
+        let deviation = deviation.min(f64::from(f32::MAX));
+        let mode = match self.mode {
+            ComparisonMode::Absolute(_) => ComparisonMode::Absolute(deviation as f32),
+            ComparisonMode::Relative(_) => {
+                let scale = result.abs().max(f64::from(f32::MIN_POSITIVE));
+                ComparisonMode::Relative((deviation / scale).min(f64::from(f32::MAX)) as f32)
+            }
+            ComparisonMode::Ulps(_) | ComparisonMode::Magnitude | ComparisonMode::LogBuckets(_) => {
+                self.mode
+            }
+        };
+        F32Wrapper {
+            inner: result as f32,
+            mode,
+            ..self
+        }
+    }
+
+    /// The inclusive range of raw floats this wrapper is `==` to, i.e. that
+    /// are within tolerance under its mode, like `(value - tolerance, value
+    /// + tolerance)` in absolute mode. Relative bands are lopsided, reaching
+    /// further away from zero than towards it. The ends are computed in
+    /// `f32`, so may be off by an ULP.
+    ///
+    /// This is the band around the value, not its hashing cell, so other
+    /// wrappers in the band can still compare unequal to this one.
+    pub fn tolerance_band(&self) -> (f32, f32) {
+        let (lo, hi) = self.mode.band(self.compared_value());
+        if self.ignore_sign {
+            // Matches by magnitude, so the band's mirror image matches too,
+            // but only one contiguous range can be returned
+            return (lo.abs().min(hi.abs()), lo.abs().max(hi.abs()));
+        }
+        (lo, hi)
+    }
+
+    pub(crate) fn has_distinct_zeros(self) -> bool {
+        self.distinct_zeros
+    }
+
+    /// The value with its sign dropped if it's ignored.
+    pub(crate) fn compared_value(self) -> f32 {
+        if self.ignore_sign {
+            self.inner.abs()
+        } else {
+            self.inner
+        }
+    }
+
+    /// The same value with its tolerance multiplied by `factor`, e.g. `1000.0`
+    /// after converting a dataset from meters to millimeters.
+    pub fn scale_tolerance_by(self, factor: f32) -> Self {
+        F32Wrapper {
+            mode: self.mode.scaled(factor),
+            ..self
+        }
+    }
+
+    /// Same verdict as `==`, but computed without branching on the values:
+    /// each criterion becomes a `0`/`1` mask and they're combined bitwise.
+    ///
+    /// This is best effort, not a cryptographic guarantee. It still branches
+    /// on the comparison mode, and the compiler is free to reintroduce
+    /// branches or the CPU to take data-dependent time on float operations.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        let same_bits = u8::from(self.to_bits() == other.to_bits());
+        let (a_nan, b_nan) = (
+            u8::from(self.inner.is_nan()),
+            u8::from(other.inner.is_nan()),
+        );
+        let signs_apart =
+            u8::from(self.sign_bit() != other.sign_bit()) & u8::from(self.distinct_zeros);
+        let same_flags = u8::from(self.distinct_zeros == other.distinct_zeros)
+            & u8::from(self.ignore_sign == other.ignore_sign);
+        let same_mode = u8::from(self.mode == other.mode);
+        let same_cells = u8::from(
+            self.mode.cell(self.compared_value()) == self.mode.cell(other.compared_value()),
+        );
+        let neither_nan = (a_nan | b_nan) ^ 1;
+        (same_flags
+            & same_mode
+            & (same_bits | (a_nan & b_nan) | (neither_nan & (signs_apart ^ 1) & same_cells)))
+            == 1
+    }
+
+    /// How many bytes [`Hash::hash`] (and so
+    /// [`F32Wrapper::canonical_hash_into`]) feeds the hasher: always a single
+    /// `write_u64`, whatever the value or mode.
+    pub const CANONICAL_BYTES: usize = 8;
+
+    /// Writes the wrapper's canonical form into `state`, byte for byte what
+    /// [`Hash::hash`] writes. Handy for folding a wrapper into a composite
+    /// hash without going through the trait.
+    pub fn canonical_hash_into<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.canonical_u64());
+    }
+
+    /// The one `u64` [`F32Wrapper::canonical_hash_into`] writes.
+    pub(crate) fn canonical_u64(&self) -> u64 {
+        if self.inner.is_nan() {
+            // No mode's cells reach `i64::MIN`, so NaNs get it to themselves
+            return grid::mix(i64::MIN);
+        }
+        let mixed = grid::mix(self.mode.canonicalize(self.compared_value()));
+        // The cell index already carries the sign of everything but zero.
+        // Folding the sign in, instead of writing it separately, keeps the
+        // hash at `CANONICAL_BYTES`
+        if self.distinct_zeros && self.sign_bit() {
+            !mixed
+        } else {
+            mixed
+        }
+    }
+
+    /// The canonical hash as a single `u64`, computed with a built-in FNV-1a
+    /// rather than `DefaultHasher`. Unlike `DefaultHasher` it's unkeyed and
+    /// stable across platforms and Rust releases, so it's safe to persist.
+    /// Equal wrappers get equal values.
+    ///
+    /// The hasher itself only needs `core`, but snapping to a grid uses
+    /// `std`'s float math, so this isn't available without `std`.
+    #[inline]
+    pub fn hash_value(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        self.canonical_hash_into(&mut hasher);
+        hasher.finish()
+    }
+
+    fn to_bits(self) -> u32 {
+        self.inner.to_bits()
+    }
+
+    /// The raw bits as `0b` followed by the sign, exponent, and mantissa bits.
+    pub fn to_bin_str(self) -> String {
+        fn bit_to_char(bit: bool) -> char {
+            if bit {
+                '1'
+            } else {
+                '0'
+            }
+        }
+        let mut s = String::with_capacity(34); // "0b" + 32 bits
+        s.push_str("0b");
+        s.push(bit_to_char(self.sign_bit()));
+        self.exponent_bits()
+            .iter()
+            .for_each(|bit| s.push(bit_to_char(*bit)));
+        self.mantissa_bits()
+            .iter()
+            .for_each(|bit| s.push(bit_to_char(*bit)));
+        s
+    }
+
+    /// The bits split into labelled sign, exponent, and mantissa fields.
+    pub fn breakdown(&self) -> BitBreakdown {
+        BitBreakdown {
+            sign: self.sign_bit(),
+            exponent: self.exponent_bits(),
+            mantissa: self.mantissa_bits(),
+        }
+    }
+
+    #[inline]
+    fn sign_bit(self) -> bool {
+        (self.to_bits() & (1 << (F32_BITS - 1))) != 0
+    }
+
+    fn exponent_bits(self) -> [bool; F32_EXPONENT_BITS] {
+        let bits = self.to_bits();
+        let mut bit_selector = 1 << (F32_BITS - 1 - 1);
+        let mut mantissa_bits = [false; F32_EXPONENT_BITS];
+
+        for bit in mantissa_bits.iter_mut().take(F32_EXPONENT_BITS) {
+            *bit = (bits & bit_selector) != 0;
+            bit_selector >>= 1;
+        }
+        mantissa_bits
+    }
+
+    fn mantissa_bits(self) -> [bool; F32_MANTISA_BITS] {
+        let bits = self.to_bits();
+        let mut bit_selector = 1 << (F32_BITS - 1 - 1 - F32_EXPONENT_BITS);
+        let mut mantissa_bits = [false; F32_MANTISA_BITS];
+
+        for bit in mantissa_bits.iter_mut().take(F32_MANTISA_BITS) {
+            *bit = (bits & bit_selector) != 0;
+            bit_selector >>= 1;
+        }
+        mantissa_bits
+    }
+}
+
+/// The fields of an `f32`'s bits, most significant bit first. A structured
+/// alternative to [`F32Wrapper::to_bin_str`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BitBreakdown {
+    pub sign: bool,
+    pub exponent: [bool; F32_EXPONENT_BITS],
+    pub mantissa: [bool; F32_MANTISA_BITS],
+}
+
+impl From<F32Wrapper> for BitBreakdown {
+    fn from(val: F32Wrapper) -> Self {
+        val.breakdown()
+    }
+}
+
+impl fmt::Display for BitBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_bits(f: &mut fmt::Formatter<'_>, bits: &[bool]) -> fmt::Result {
+            bits.iter()
+                .try_for_each(|bit| write!(f, "{}", u8::from(*bit)))
+        }
+
+        let sign = if self.sign { '-' } else { '+' };
+        writeln!(f, "sign:     {} ({sign})", u8::from(self.sign))?;
+        write!(f, "exponent: ")?;
+        write_bits(f, &self.exponent)?;
+        write!(f, "\nmantissa: ")?;
+        write_bits(f, &self.mantissa)
+    }
+}
+
+/// Values are hashed by the grid cell they snap to under their mode (a
+/// tolerance-wide one, by default). Consecutive cells would otherwise feed
+/// consecutive integers to the hasher, so the cell index goes through an
+/// avalanche finalizer first. The finalizer is a bijection, so values sharing
+/// a cell still hash equal; it just spreads neighbouring cells across the
+/// hash space.
+///
+/// Equality goes by the same cells, so equal wrappers always hash alike. The
+/// cursed part moved: two values within tolerance that straddle a cell edge
+/// are now simply unequal.
+impl Hash for F32Wrapper {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical_hash_into(state);
+    }
+}
+
+#[test]
+fn it_rejects_negative_and_nan_tolerances() {
+    assert!(F32Wrapper::try_with_tolerance(1.0, -0.001).is_err());
+    assert!(F32Wrapper::try_with_tolerance(1.0, f32::NAN).is_err());
+    assert!(F32Wrapper::try_with_mode(1.0, ComparisonMode::Relative(f32::INFINITY)).is_err());
+    assert!(F32Wrapper::try_with_tolerance(1.0, 0.0).is_ok());
+    assert!(
+        F32Wrapper::try_with_tolerance(1.0, 0.001)
+            .unwrap()
+            .tolerance()
+            == 0.001
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "invalid tolerance")]
+fn it_asserts_on_a_negative_tolerance_in_debug() {
+    F32Wrapper::with_tolerance(1.0, -0.001);
+}
+
+#[test]
+fn it_treats_pos_and_neg_zero_the_same() {
+    let pos_zero = F32Wrapper::new(0.0);
+    let neg_zero = F32Wrapper::new(-0.0);
+
+    let mut set = HashSet::new();
+    set.insert(pos_zero);
+    set.insert(neg_zero);
+
+    assert!(set.len() == 1);
+}
+
+#[test]
+fn it_can_treat_pos_and_neg_zero_as_different() {
+    let pos_zero = F32Wrapper::with_distinct_zeros(0.0);
+    let neg_zero = F32Wrapper::with_distinct_zeros(-0.0);
+
+    let mut set = HashSet::new();
+    set.insert(pos_zero);
+    set.insert(neg_zero);
+
+    assert!(set.len() == 2);
+}
+
+#[test]
+fn it_never_equates_distinct_and_merged_zero_wrappers() {
+    let distinct = F32Wrapper::with_distinct_zeros(-0.0);
+    let merged = F32Wrapper::new(-0.0);
+
+    assert!(distinct != merged);
+    assert!(merged != distinct);
+    assert!(!distinct.ct_eq(&merged));
+    assert!(distinct.hash_value() != merged.hash_value());
+    assert!(F32Wrapper::with_distinct_zeros(1.0) != F32Wrapper::new(1.0));
+    assert!(distinct == F32Wrapper::with_distinct_zeros(-0.0));
+}
+
+#[test]
+fn it_snaps_half_a_tolerance_either_side_of_zero_to_zero() {
+    let zero = F32Wrapper::new(0.0);
+    let above = F32Wrapper::new(F32_ERROR_TOLERANCE / 2.0);
+    let below = F32Wrapper::new(-F32_ERROR_TOLERANCE / 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(above);
+    set.insert(zero);
+    set.insert(below);
+
+    assert!(above == zero && below == zero);
+    assert!(set.len() == 1);
+    assert!(F32Wrapper::new((F32_ERROR_TOLERANCE / 2.0).next_up()) != zero);
+}
+
+#[test]
+fn it_writes_canonical_bytes_to_the_hasher() {
+    #[derive(Default)]
+    struct CountingHasher(usize);
+    impl Hasher for CountingHasher {
+        fn finish(&self) -> u64 {
+            0
+        }
+        fn write(&mut self, bytes: &[u8]) {
+            self.0 += bytes.len();
+        }
+    }
+
+    for wrapper in [
+        F32Wrapper::new(1.5),
+        F32Wrapper::new(f32::NAN),
+        F32Wrapper::with_distinct_zeros(-0.0),
+        F32Wrapper::with_mode(f32::MAX, ComparisonMode::Ulps(4)),
+        F32Wrapper::with_magnitude_buckets(-3.0),
+    ] {
+        let mut hasher = CountingHasher::default();
+        wrapper.hash(&mut hasher);
+        assert!(hasher.0 == F32Wrapper::CANONICAL_BYTES);
+    }
+}
+
+#[test]
+fn it_can_ignore_sign_entirely() {
+    let mut set = HashSet::new();
+    set.insert(F32Wrapper::with_sign_ignored(3.0));
+    set.insert(F32Wrapper::with_sign_ignored(-3.0));
+    set.insert(F32Wrapper::with_sign_ignored(-3.000001));
+    assert!(set.len() == 1);
+
+    let mut set = HashSet::new();
+    set.insert(F32Wrapper::new(3.0));
+    set.insert(F32Wrapper::new(-3.0));
+    assert!(set.len() == 2);
+
+    let wrapper = F32Wrapper::with_sign_ignored(-3.0);
+    assert!(wrapper == 3.0f32 && wrapper > 2.0 && wrapper < -4.0);
+}
+
+#[test]
+fn it_keeps_ignored_signs_transitive() {
+    let magnitude = F32Wrapper::with_sign_ignored(3.0);
+    let (pos, neg) = (F32Wrapper::new(3.0), F32Wrapper::new(-3.0));
+
+    assert!(pos != neg);
+    assert!(magnitude != pos && magnitude != neg);
+    assert!(!magnitude.ct_eq(&pos) && !neg.ct_eq(&magnitude));
+    assert!(magnitude == F32Wrapper::with_sign_ignored(-3.0));
+}
+
+#[test]
+fn it_builds_with_the_thread_default_tolerance() {
+    assert!(default_tolerance() == F32_ERROR_TOLERANCE);
+    set_default_tolerance(0.1);
+    let (a, b) = (F32Wrapper::new(1.0), F32Wrapper::new(1.04));
+    let other_thread = std::thread::spawn(default_tolerance).join().unwrap();
+    set_default_tolerance(F32_ERROR_TOLERANCE);
+
+    assert!(a.tolerance() == 0.1);
+    assert!(a == b);
+    assert!(F32Wrapper::new(1.0) != F32Wrapper::new(1.04));
+    assert!(other_thread == F32_ERROR_TOLERANCE);
+}
+
+#[test]
+fn it_hashes_integers_by_the_same_grid_as_their_neighbours() {
+    let mut set = HashSet::new();
+    for i in 0..1000u16 {
+        set.insert(F32Wrapper::new(f32::from(i)));
+        set.insert(F32Wrapper::new(f32::from(i)));
+    }
+    assert!(set.len() == 1000);
+
+    // No separate integer hash: a neighbour sharing the integer's cell has
+    // to hash alike
+    let (whole, near) = (F32Wrapper::new(7.0), F32Wrapper::new(7.000002));
+    assert!(whole == near);
+    assert!(whole.hash_value() == near.hash_value());
+    assert!(set.contains(&near));
+}
+#[cfg(test)]
+fn default_hash(val: F32Wrapper) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    val.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn it_hashes_every_equal_pair_alike() {
+    // Includes pairs within tolerance that straddle a cell edge, which
+    // must compare unequal rather than equal with different hashes
+    let base = 42.0f32;
+    let steps: Vec<f32> = (0..64u8)
+        .map(|i| base + F32_ERROR_TOLERANCE * f32::from(i) / 16.0)
+        .collect();
+    for a in &steps {
+        for b in &steps {
+            let (a, b) = (F32Wrapper::new(*a), F32Wrapper::new(*b));
+            if a == b {
+                assert!(default_hash(a) == default_hash(b));
+            }
+        }
+    }
+}
+
+#[test]
+fn it_buckets_logarithmically() {
+    let hash_of = |val| {
+        let mut hasher = DefaultHasher::new();
+        F32Wrapper::with_log_buckets(val, 0.05).hash(&mut hasher);
+        hasher.finish()
+    };
+    let hundred = F32Wrapper::with_log_buckets(100.0, 0.05);
+
+    assert!(hundred == F32Wrapper::with_log_buckets(101.0, 0.05));
+    assert!(hash_of(100.0) == hash_of(101.0));
+    assert!(hundred != F32Wrapper::with_log_buckets(110.0, 0.05));
+    assert!(hash_of(100.0) != hash_of(110.0));
+    assert!(hundred != F32Wrapper::with_log_buckets(-100.0, 0.05));
+    assert!(
+        F32Wrapper::with_log_buckets(-100.0, 0.05) == F32Wrapper::with_log_buckets(-101.0, 0.05)
+    );
+    assert!(F32Wrapper::with_log_buckets(0.0, 0.05) == F32Wrapper::with_log_buckets(-0.0, 0.05));
+    assert!(
+        F32Wrapper::with_log_buckets(0.0, 0.05)
+            != F32Wrapper::with_log_buckets(f32::from_bits(1), 0.05)
+    );
+    // The same 5% band a million times smaller
+    assert!(
+        F32Wrapper::with_log_buckets(1e-4, 0.05) == F32Wrapper::with_log_buckets(1.01e-4, 0.05)
+    );
+    let (lo, hi) = hundred.tolerance_band();
+    assert!(lo <= 100.0 && (101.0..110.0).contains(&hi));
+    assert!(hundred == F32Wrapper::with_log_buckets(lo, 0.05));
+}
+
+#[test]
+fn it_treats_close_pos_numbers_as_the_same_1() {
+    let num_1 = F32Wrapper::new(42.0);
+    let num_2 = F32Wrapper::new(42.0 - F32_ERROR_TOLERANCE / 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 1);
+    assert!(num_1 == num_2);
+    assert!(default_hash(num_1) == default_hash(num_2));
+}
+
+#[test]
+fn it_treats_close_pos_numbers_as_the_same_2() {
+    let num_1 = F32Wrapper::new(42.0);
+    let num_2 = F32Wrapper::new(42.0 + F32_ERROR_TOLERANCE / 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 1);
+    assert!(num_1 == num_2);
+    assert!(default_hash(num_1) == default_hash(num_2));
+}
+
+#[test]
+fn it_treats_close_neg_numbers_as_the_same_2() {
+    let num_1 = F32Wrapper::new(-42.0);
+    let num_2 = F32Wrapper::new(-42.0 - F32_ERROR_TOLERANCE / 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 1);
+    assert!(num_1 == num_2);
+    assert!(default_hash(num_1) == default_hash(num_2));
+}
+
+#[test]
+fn it_treats_close_neg_numbers_as_the_same_1() {
+    let num_1 = F32Wrapper::new(-42.0);
+    let num_2 = F32Wrapper::new(-42.0 + F32_ERROR_TOLERANCE / 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 1);
+    assert!(num_1 == num_2);
+    assert!(default_hash(num_1) == default_hash(num_2));
+}
+
+#[test]
+fn it_treats_non_close_pos_numbers_as_different_1() {
+    let num_1 = F32Wrapper::new(42.0);
+    let num_2 = F32Wrapper::new(42.0 - F32_ERROR_TOLERANCE * 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 2);
+    assert!(num_1 != num_2);
+    assert!(default_hash(num_1) != default_hash(num_2));
+}
+
+#[test]
+fn it_treats_non_close_neg_numbers_as_different_2() {
+    let num_1 = F32Wrapper::new(-42.0);
+    let num_2 = F32Wrapper::new(-42.0 + F32_ERROR_TOLERANCE * 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 2);
+    assert!(num_1 != num_2);
+    assert!(default_hash(num_1) != default_hash(num_2));
+}
+
+#[test]
+fn it_treats_non_close_neg_numbers_as_different_1() {
+    let num_1 = F32Wrapper::new(-42.0);
+    let num_2 = F32Wrapper::new(-42.0 - F32_ERROR_TOLERANCE * 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 2);
+    assert!(num_1 != num_2);
+    assert!(default_hash(num_1) != default_hash(num_2));
+}
+
+#[test]
+fn it_treats_non_close_pos_numbers_as_different_2() {
+    let num_1 = F32Wrapper::new(42.0);
+    let num_2 = F32Wrapper::new(42.0 + F32_ERROR_TOLERANCE * 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(set.len() == 2);
+    assert!(num_1 != num_2);
+    assert!(default_hash(num_1) != default_hash(num_2));
+}
+
+#[test]
+fn it_reports_the_band_of_matching_values() {
+    let (lo, hi) = F32Wrapper::with_tolerance(10.0, 0.5).tolerance_band();
+    assert!(lo == 9.5 && hi == 10.5);
+
+    let (lo, hi) = F32Wrapper::with_relative(-100.0, 0.01).tolerance_band();
+    assert!((lo + 100.0 / 0.99).abs() < 1e-4 && (hi + 99.0).abs() < 1e-4);
+    let relative = F32Wrapper::with_relative(-100.0, 0.01);
+    assert!(relative == -101.0f32 && relative != -101.1f32);
+
+    let (lo, hi) = F32Wrapper::with_mode(1.0, ComparisonMode::Ulps(2)).tolerance_band();
+    assert!(lo == 1.0f32.next_down().next_down() && hi == 1.0f32.next_up().next_up());
+    let (lo, hi) = F32Wrapper::with_magnitude_buckets(3.0).tolerance_band();
+    assert!(lo == 2.0 && hi == 4.0f32.next_down());
+}
+
+#[test]
+fn it_fails_checked_arithmetic_on_non_finite_results() {
+    let max = F32Wrapper::new(f32::MAX);
+    let two = F32Wrapper::new(2.0);
+
+    assert!(max.checked_mul(two).is_none());
+    assert!(max.checked_add(max).is_none());
+    assert!(F32Wrapper::new(-f32::MAX).checked_sub(max).is_none());
+    assert!(two.checked_div(F32Wrapper::new(0.0)).is_none());
+    assert!(two.checked_add(F32Wrapper::new(f32::NAN)).is_none());
+    assert!(max.checked_div(two).map(F32Wrapper::value) == Some(f32::MAX / 2.0));
+    let relative = F32Wrapper::with_relative(3.0, 0.1);
+    assert!(relative.checked_sub(two).map(F32Wrapper::mode) == Some(relative.mode()));
+}
+
+#[test]
+fn it_never_equates_different_modes() {
+    let absolute = F32Wrapper::with_tolerance(1.0, 0.1);
+    let relative = F32Wrapper::with_relative(1.0, 0.1);
+
+    assert!(absolute != relative);
+    assert!(absolute.hash_value() != relative.hash_value());
+    assert!(absolute != F32Wrapper::with_relative(1.01, 0.1));
+    assert!(absolute != F32Wrapper::with_tolerance(1.0, 0.2));
+    assert!(absolute == F32Wrapper::with_tolerance(1.01, 0.1));
+    assert!(F32Wrapper::with_tolerance(f32::NAN, 0.1) != F32Wrapper::with_relative(f32::NAN, 0.1));
+    assert!(!absolute.ct_eq(&relative) && absolute.ct_eq(&F32Wrapper::with_tolerance(1.01, 0.1)));
+}
+
+#[test]
+fn it_propagates_tolerance_through_powers() {
+    let root = F32Wrapper::new(4.0).sqrt();
+    assert!(root.value() == 2.0 && root != F32Wrapper::new(2.0));
+    assert!((root.tolerance() - F32_ERROR_TOLERANCE / 4.0).abs() < 1e-10);
+    assert!(root == 2.000002f32 && root != 2.00001f32);
+
+    let cube = F32Wrapper::with_tolerance(2.0, 0.001).powi(3);
+    assert!(cube.value() == 8.0);
+    assert!((cube.tolerance() - 12.0 * 0.001).abs() < 1e-4);
+
+    // 2^3 with an exact base: `|2^3 · ln 2| · 0.001`
+    let power = F32Wrapper::with_tolerance(2.0, 0.0).powf(F32Wrapper::with_tolerance(3.0, 0.001));
+    assert!((power.tolerance() - 8.0 * 2.0f32.ln() * 0.001).abs() < 1e-4);
+
+    let relative = F32Wrapper::with_relative(9.0, 0.02).sqrt();
+    assert!(relative.value() == 3.0);
+    assert!(matches!(relative.mode(), ComparisonMode::Relative(tol) if (tol - 0.01).abs() < 1e-3));
+    assert!(F32Wrapper::new(0.0).sqrt().tolerance().is_finite());
+}
+
+#[test]
+fn it_treats_identical_nans_as_the_same() {
+    let nan_1 = F32Wrapper::new(f32::NAN);
+    let nan_2 = F32Wrapper::new(f32::NAN);
+
+    assert!(nan_1 == nan_2);
+    assert!(default_hash(nan_1) == default_hash(nan_2));
+
+    let mut set = HashSet::new();
+    set.insert(nan_1);
+    set.insert(nan_2);
+
+    assert!(set.len() == 1);
+}
+
+#[test]
+fn it_merges_previously_distinct_values_after_scaling_tolerance() {
+    let num_1 = F32Wrapper::new(1.0);
+    let num_2 = F32Wrapper::new(1.00003);
+    assert!(num_1 != num_2);
+
+    let num_1 = num_1.scale_tolerance_by(10.0);
+    let num_2 = num_2.scale_tolerance_by(10.0);
+
+    let mut set = HashSet::new();
+    set.insert(num_1);
+    set.insert(num_2);
+
+    assert!(num_1 == num_2);
+    assert!(set.len() == 1);
+}
+
+#[test]
+fn it_treats_values_within_a_percentage_as_the_same() {
+    let mut set = HashSet::new();
+    set.insert(F32Wrapper::with_percent(100.0, 2.0));
+    set.insert(F32Wrapper::with_percent(101.0, 2.0));
+    assert!(set.len() == 1);
+
+    assert!(F32Wrapper::with_percent(100.0, 0.5) != F32Wrapper::with_percent(101.0, 0.5));
+}
+
+#[test]
+fn it_falls_back_near_zero_in_relative_mode() {
+    let zero = F32Wrapper::with_percent(0.0, 2.0);
+    let subnormal = F32Wrapper::with_percent(1e-40, 2.0);
+
+    let mut set = HashSet::new();
+    set.insert(zero);
+    set.insert(subnormal);
+
+    assert!(zero == subnormal);
+    assert!(set.len() == 1);
+}
+
+#[test]
+fn it_writes_the_same_canonical_bytes_as_hash() {
+    for val in [
+        F32Wrapper::new(42.0),
+        F32Wrapper::with_distinct_zeros(-0.0),
+        F32Wrapper::with_percent(-7.5, 1.0),
+    ] {
+        let mut via_trait = DefaultHasher::new();
+        val.hash(&mut via_trait);
+        let mut via_method = DefaultHasher::new();
+        val.canonical_hash_into(&mut via_method);
+
+        assert!(via_trait.finish() == via_method.finish());
+    }
+}
+
+#[test]
+fn it_agrees_with_eq_in_constant_time_eq() {
+    let values = [
+        0.0,
+        -0.0,
+        1e-40,
+        F32_ERROR_TOLERANCE / 2.0,
+        1.0,
+        1.000001,
+        1.0001,
+        -42.0,
+        -42.000004,
+        100.0,
+        101.0,
+        f32::MAX,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+        f32::NAN,
+    ];
+    let wrappers: Vec<fn(f32) -> F32Wrapper> = vec![
+        F32Wrapper::new,
+        F32Wrapper::with_distinct_zeros,
+        F32Wrapper::with_sign_ignored,
+        |val| F32Wrapper::with_percent(val, 2.0),
+        |val| F32Wrapper::with_mode(val, ComparisonMode::Ulps(8)),
+    ];
+
+    for wrap_a in &wrappers {
+        for wrap_b in &wrappers {
+            for a in values {
+                for b in values {
+                    let (a, b) = (wrap_a(a), wrap_b(b));
+                    assert!(a.ct_eq(&b) == (a == b), "{a:?} vs {b:?}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn it_only_merges_identical_values_at_zero_tolerance() {
+    let one = F32Wrapper::with_tolerance(1.0, 0.0);
+    let next = F32Wrapper::with_tolerance(1.0f32.next_up(), 0.0);
+
+    let mut set = HashSet::new();
+    set.insert(one);
+    set.insert(next);
+    set.insert(one);
+    set.insert(F32Wrapper::with_tolerance(f32::MAX, 0.0));
+    set.insert(F32Wrapper::with_tolerance(f32::MAX.next_down(), 0.0));
+
+    assert!(one != next);
+    assert!(set.len() == 4);
+}
+
+#[test]
+fn it_buckets_small_magnitudes_by_their_own_tolerance() {
+    // The cutoff used to be the global constant, however small the values
+    // or the wrapper's tolerance. Grid cells scale with the tolerance instead
+    let values = [1e-9, 1.00001e-9, 3e-9, 2e-38, 2.00001e-38];
+    let bucket_count = |tolerance| {
+        values
+            .iter()
+            .map(|val| F32Wrapper::with_tolerance(*val, tolerance))
+            .collect::<HashSet<_>>()
+            .len()
+    };
+
+    assert!(bucket_count(F32_ERROR_TOLERANCE) == 1);
+    assert!(bucket_count(1e-12) == 3);
+    // Only the two values near 2e-38 are still within a tolerance this fine
+    assert!(bucket_count(1e-40) == 4);
+}
+
+#[test]
+fn it_derives_tolerance_from_the_ulp_at_the_value() {
+    let small = F32Wrapper::auto_tolerance(1.0, 4);
+    let large = F32Wrapper::auto_tolerance(1048576.0, 4);
+
+    assert!(small.mode() == ComparisonMode::Absolute(4.0 * f32::EPSILON));
+    assert!(large.tolerance() == small.tolerance() * 1048576.0);
+    assert!(large == 1048576.0 + 0.5);
+    assert!(large != 1048576.0 + 0.625);
+    assert!(F32Wrapper::auto_tolerance(f32::INFINITY, 4).tolerance() == 0.0);
+}
+
+#[test]
+fn it_steps_to_adjacent_representable_floats() {
+    for val in [
+        1.0,
+        -3.5,
+        0.0,
+        -0.0,
+        f32::MIN_POSITIVE,
+        f32::MAX.next_down(),
+    ] {
+        let val = F32Wrapper::new(val);
+        for neighbour in [
+            val.next_representable_above(),
+            val.next_representable_below(),
+        ] {
+            let explanation = crate::explain_eq(val.value(), neighbour.value(), val.mode());
+            assert!(explanation.ulp_distance == 1);
+            assert!(neighbour.mode() == val.mode());
+        }
+    }
+
+    assert!(F32Wrapper::new(0.0).next_representable_above().value() == f32::from_bits(1));
+    assert!(F32Wrapper::new(f32::MAX).next_representable_above().value() == f32::INFINITY);
+    assert!(F32Wrapper::new(1.0).next_representable_below().value() == 1.0 - f32::EPSILON / 2.0);
+}
+
+#[test]
+fn it_buckets_by_sign_and_exponent_in_magnitude_mode() {
+    let hash_of = |val| {
+        let mut hasher = DefaultHasher::new();
+        F32Wrapper::with_magnitude_buckets(val).hash(&mut hasher);
+        hasher.finish()
+    };
+    let one = F32Wrapper::with_magnitude_buckets(1.0);
+
+    assert!(one == F32Wrapper::with_magnitude_buckets(1.9));
+    assert!(hash_of(1.0) == hash_of(1.9));
+    assert!(one != F32Wrapper::with_magnitude_buckets(2.0));
+    assert!(hash_of(1.0) != hash_of(2.0));
+    assert!(one != F32Wrapper::with_magnitude_buckets(-1.5));
+    assert!(one.tolerance() == 1.0);
+}
+
+#[test]
+fn it_hashes_values_like_their_quantized_twins() {
+    let hash_of = |val: F32Wrapper| {
+        let mut hasher = DefaultHasher::new();
+        val.hash(&mut hasher);
+        hasher.finish()
+    };
+    for tolerance in [F32_ERROR_TOLERANCE, 0.001, 0.37, 1e-30] {
+        for i in 0..20_000u16 {
+            let magnitude = (f32::from(i) * 0.7331).powf(1.7) * 10f32.powi(i32::from(i % 9) - 4);
+            for val in [magnitude, -magnitude] {
+                let twin = snap_to_grid(val, tolerance, Rounding::HalfTowardZero);
+                let (val, twin) = (
+                    F32Wrapper::with_tolerance(val, tolerance),
+                    F32Wrapper::with_tolerance(twin, tolerance),
+                );
+
+                assert!(val == twin, "{val:?} vs {twin:?}");
+                assert!(hash_of(val) == hash_of(twin), "{val:?} vs {twin:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn it_converts_to_fixed_point() {
+    let fixed = |val: f32, scale| F32Wrapper::new(val).to_fixed_point(scale);
+
+    assert!(fixed(42.000004, 5) == Some(4_200_000));
+    assert!(fixed(41.999996, 5) == Some(4_200_000));
+    assert!(fixed(-1.25, 1) == Some(-13));
+    assert!(fixed(0.5, 0) == Some(1));
+    assert!(fixed(f32::MAX, 0).is_none());
+    assert!(fixed(1.0, 400).is_none());
+    assert!(fixed(f32::NAN, 2).is_none());
+}
+
+#[test]
+fn it_snaps_to_si_prefixed_grids() {
+    assert!(F32Wrapper::with_milli(2.0001) == F32Wrapper::with_milli(2.0003));
+    assert!(F32Wrapper::with_milli(2.0001) != F32Wrapper::with_milli(2.0011));
+    assert!(F32Wrapper::with_micro(0.5000001) == F32Wrapper::with_micro(0.5000003));
+    assert!(F32Wrapper::with_micro(0.5000001) != F32Wrapper::with_micro(0.5000011));
+    assert!(F32Wrapper::with_nano(3e-6) == F32Wrapper::with_nano(3.0002e-6));
+    assert!(F32Wrapper::with_nano(3e-6) != F32Wrapper::with_nano(3.002e-6));
+    assert!(F32Wrapper::with_nano(1.0).tolerance() == 1e-9);
+}
+
+#[test]
+fn it_infers_tolerance_from_decimal_places() {
+    let parse = |s| F32Wrapper::from_str_inferred_tolerance(s).unwrap();
+
+    assert!(parse("1.2").tolerance() == 0.05);
+    assert!(parse("1.20").tolerance() == 0.005);
+    assert!(parse(" 1.250\n").tolerance() == 0.0005);
+    assert!(parse("-7").tolerance() == 0.5);
+    assert!(parse("12e3").tolerance() == 500.0);
+    assert!(parse("1.5E-3").tolerance() == 0.00005);
+    assert!(parse("1.2").value() == 1.2);
+    assert!(F32Wrapper::from_str_inferred_tolerance("1.2.3").is_err());
+}
+
+#[test]
+fn it_computes_stable_hash_values() {
+    assert!(F32Wrapper::new(1.0).hash_value() == F32Wrapper::new(1.000004).hash_value());
+    assert!(F32Wrapper::new(1.0).hash_value() != F32Wrapper::new(2.0).hash_value());
+    // Golden value; this must never change
+    assert!(F32Wrapper::new(1.0).hash_value() == 0x5b05_ad25_d2b1_7a25);
+}
+
+#[test]
+fn it_compares_against_raw_thresholds() {
+    let val = F32Wrapper::with_tolerance(10.0, 0.01);
+
+    assert!(val < 10.5);
+    assert!(!(val < 10.005));
+    assert!(val.partial_cmp(&10.005) == Some(Ordering::Equal));
+    assert!(val == 9.995);
+    assert!(val > 9.5);
+    assert!(val.partial_cmp(&f32::NAN).is_none());
+}
+
+#[test]
+fn it_breaks_down_one() {
+    let breakdown = F32Wrapper::new(1.0).breakdown();
+
+    assert!(!breakdown.sign);
+    assert!(breakdown.exponent == [false, true, true, true, true, true, true, true]);
+    assert!(breakdown.mantissa == [false; F32_MANTISA_BITS]);
+    assert!(
+        breakdown.to_string()
+            == "sign:     0 (+)\nexponent: 01111111\nmantissa: 00000000000000000000000"
+    );
+}
+
+#[test]
+fn it_handles_the_largest_exponent_fields() {
+    // Formerly reconstructed with `2i32.pow`; make sure the top of the
+    // exponent range neither panics nor comes out wrong
+    let max = F32Wrapper::new(f32::MAX);
+    let inf = F32Wrapper::new(f32::INFINITY);
+    let nan = F32Wrapper::new(f32::NAN);
+
+    assert!(max.breakdown().exponent == [true, true, true, true, true, true, true, false]);
+    assert!(inf.breakdown().exponent == [true; F32_EXPONENT_BITS]);
+    assert!(nan.breakdown().exponent == [true; F32_EXPONENT_BITS]);
+    for mode in [
+        ComparisonMode::Absolute(F32_ERROR_TOLERANCE),
+        ComparisonMode::Relative(F32_ERROR_TOLERANCE),
+        ComparisonMode::Ulps(4),
+    ] {
+        let hashes: HashSet<u64> = [max, inf, nan]
+            .map(|val| {
+                let mut hasher = DefaultHasher::new();
+                F32Wrapper::with_mode(val.value(), mode).hash(&mut hasher);
+                hasher.finish()
+            })
+            .into_iter()
+            .collect();
+        assert!(hashes.len() == 3);
+    }
+}
+
+#[test]
+fn it_shows_the_mode_in_debug_output() {
+    let val = F32Wrapper::with_relative(2.5, 1e-6);
+
+    assert!(format!("{val:?}") == "F32Wrapper { value: 2.5, mode: Relative(1e-6) }");
+    assert!(format!("{:?}", F32Wrapper::with_distinct_zeros(-0.0)).contains("distinct_zeros: true"));
+}
+
+#[test]
+fn it_formats_bin_strs_of_ieee_values() {
+    let cases = [
+        (1.0, "0b00111111100000000000000000000000"),
+        (-2.0, "0b11000000000000000000000000000000"),
+        (0.5, "0b00111111000000000000000000000000"),
+        (0.1, "0b00111101110011001100110011001101"),
+    ];
+
+    for (val, expected) in cases {
+        assert!(F32Wrapper::new(val).to_bin_str() == expected);
+    }
+}
+#[cfg(test)]
+#[derive(Default)]
+struct IdentityHasher(u64);
+
+#[cfg(test)]
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 << 8) | u64::from(*byte);
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+}
+
+#[test]
+fn it_spreads_consecutive_cells_across_the_hash_space() {
+    // Even a hasher that passes its input straight through should see
+    // consecutive cells land all over the place, in the low bits used to
+    // pick a bucket and in the high bits used as a tag
+    let mut low_byte_counts = [0usize; 256];
+    let mut high_tags = HashSet::new();
+    for i in 0..4096u16 {
+        let mut hasher = IdentityHasher::default();
+        F32Wrapper::new(f32::from(i) * F32_ERROR_TOLERANCE).hash(&mut hasher);
+        let hash = hasher.finish();
+        low_byte_counts[(hash & 0xff) as usize] += 1;
+        high_tags.insert(hash >> 57);
+    }
+
+    // 16 per bucket on average
+    assert!(low_byte_counts
+        .iter()
+        .all(|count| *count > 0 && *count <= 32));
+    assert!(high_tags.len() == 128);
+}
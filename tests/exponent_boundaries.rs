@@ -0,0 +1,46 @@
+//! Standing regression suite for the demo's lament: values a hair apart that
+//! sit on either side of a power of two used to land in different buckets,
+//! because the hash only looked at the sign and exponent bits.
+//!
+//! A plain `HashSet<F32Wrapper>` still can't promise this (any hash has cell
+//! edges that close values straddle), so these go through `FloatHashSet`,
+//! which probes neighbouring cells.
+
+use floaty_hash::{FloatHashSet, F32_ERROR_TOLERANCE};
+
+fn values_around_powers_of_two() -> impl Iterator<Item = f32> {
+    (-10..=10).flat_map(|exp| {
+        let power = 2.0f32.powi(exp);
+        [power.next_down(), power, power.next_up()]
+    })
+}
+
+fn assert_merges(a: f32, b: f32) {
+    let mut set = FloatHashSet::new(F32_ERROR_TOLERANCE);
+    set.insert(a);
+    set.insert(b);
+
+    assert!(set.len() == 1, "{a} and {b} didn't merge");
+}
+
+#[test]
+fn it_merges_values_nudged_up_across_powers_of_two() {
+    for val in values_around_powers_of_two() {
+        assert_merges(val, val + F32_ERROR_TOLERANCE / 2.0);
+    }
+}
+
+#[test]
+fn it_merges_values_nudged_down_across_powers_of_two() {
+    for val in values_around_powers_of_two() {
+        assert_merges(val, val - F32_ERROR_TOLERANCE / 2.0);
+    }
+}
+
+#[test]
+fn it_merges_negative_values_across_powers_of_two() {
+    for val in values_around_powers_of_two().map(|val| -val) {
+        assert_merges(val, val + F32_ERROR_TOLERANCE / 2.0);
+        assert_merges(val, val - F32_ERROR_TOLERANCE / 2.0);
+    }
+}
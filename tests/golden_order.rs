@@ -0,0 +1,34 @@
+//! Golden-order tests: a fixed-seed `FloatHashSet` fed a known sequence must
+//! iterate in exactly the recorded order. Any change to canonicalization,
+//! cell mixing, or bucket layout that would reshuffle iteration fails here,
+//! so it can't slip out unnoticed to users relying on deterministic output.
+//!
+//! The order also depends on std's `DefaultHasher` and `HashMap`, which may
+//! change between Rust releases. If a toolchain bump is the only change and
+//! these fail, regenerate the golden vectors (and call it out in the release
+//! notes).
+
+use floaty_hash::FloatHashSet;
+
+fn order_of(values: &[f32], tolerance: f32, seed: u64) -> Vec<f32> {
+    let mut set = FloatHashSet::with_fixed_seed(tolerance, seed);
+    set.extend(values.iter().copied());
+    set.iter().collect()
+}
+
+#[test]
+fn it_iterates_small_integers_in_golden_order() {
+    let values: Vec<f32> = (0..10u8).map(f32::from).collect();
+
+    assert!(order_of(&values, 0.001, 42) == [2.0, 0.0, 6.0, 3.0, 4.0, 9.0, 7.0, 1.0, 8.0, 5.0]);
+}
+
+#[test]
+fn it_iterates_clustered_values_in_golden_order() {
+    let values = [
+        0.5, -1.25, 3.0, 0.500004, 1e6, -0.0, 0.0, 2.75, 3.000001, -8.5,
+    ];
+
+    // Near-duplicates fold into the first representative of their cluster
+    assert!(order_of(&values, 0.00001, 7) == [-0.0, 3.0, 2.75, -8.5, -1.25, 0.5, 1e6]);
+}
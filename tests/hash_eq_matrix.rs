@@ -0,0 +1,232 @@
+//! The `Hash`/`Eq` contract (equal wrappers hash alike) checked for every
+//! comparison mode over one pool of awkward values: zeros, subnormals, cell
+//! edges, powers of two and their neighbours, the extremes, and NaNs. Pairs
+//! are also drawn across modes, tolerances and flags, and across the other
+//! wrapper types, since that's where mismatched configurations hide.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+use floaty_hash::{
+    CanonicalF32, ComparisonMode, F32Wrapper, F64Wrapper, FloatArrayWrapper, SignalWrapper,
+    F32_ERROR_TOLERANCE,
+};
+
+fn tricky_values() -> Vec<f32> {
+    let mut values = vec![
+        0.0,
+        -0.0,
+        f32::from_bits(1),
+        -f32::from_bits(1),
+        f32::MIN_POSITIVE,
+        F32_ERROR_TOLERANCE / 2.0,
+        -F32_ERROR_TOLERANCE / 2.0,
+        F32_ERROR_TOLERANCE * 1.5,
+        0.005,
+        0.015,
+        0.1,
+        1.0 / 3.0,
+        42.0,
+        -42.000004,
+        1e7,
+        f32::MAX,
+        f32::MIN,
+        f32::INFINITY,
+        f32::NEG_INFINITY,
+        f32::NAN,
+        -f32::NAN,
+        f32::from_bits(f32::NAN.to_bits() | 1),
+    ];
+    for exp in [-20, -1, 0, 1, 23, 24, 100] {
+        let power = 2.0f32.powi(exp);
+        values.extend([power.next_down(), power, power.next_up()]);
+        values.extend([power + F32_ERROR_TOLERANCE, power - F32_ERROR_TOLERANCE]);
+    }
+    values
+        .iter()
+        .flat_map(|val| [*val, val.next_up(), val.next_down()])
+        .collect()
+}
+
+fn modes() -> Vec<(&'static str, ComparisonMode)> {
+    vec![
+        ("absolute", ComparisonMode::Absolute(F32_ERROR_TOLERANCE)),
+        ("absolute, exact", ComparisonMode::Absolute(0.0)),
+        // What `from_str_inferred_tolerance` picks for two decimal places
+        ("decimal places", ComparisonMode::Absolute(0.005)),
+        ("relative", ComparisonMode::Relative(1e-4)),
+        ("ulps", ComparisonMode::Ulps(4)),
+        ("magnitude", ComparisonMode::Magnitude),
+        ("log buckets", ComparisonMode::LogBuckets(0.01)),
+    ]
+}
+
+fn hash_of<T: Hash>(val: T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    val.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn it_hashes_equal_wrappers_alike_in_every_mode() {
+    let values = tricky_values();
+    for (name, mode) in modes() {
+        for a in &values {
+            for b in &values {
+                let (a, b) = (
+                    F32Wrapper::with_mode(*a, mode),
+                    F32Wrapper::with_mode(*b, mode),
+                );
+                if a == b {
+                    assert!(
+                        hash_of(a) == hash_of(b),
+                        "{name} mode: {a:?} == {b:?} but they hash differently"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn it_keeps_eq_reflexive_and_symmetric_in_every_mode() {
+    let values = tricky_values();
+    for (name, mode) in modes() {
+        for a in &values {
+            let a = F32Wrapper::with_mode(*a, mode);
+            assert!(a == a, "{name} mode: {a:?} isn't equal to itself");
+            for b in &values {
+                let b = F32Wrapper::with_mode(*b, mode);
+                assert!((a == b) == (b == a), "{name} mode: {a:?} and {b:?}");
+            }
+        }
+    }
+}
+
+type Constructor = Box<dyn Fn(f32) -> F32Wrapper>;
+
+/// Every way of building a wrapper that the matrix mixes: each mode, plus
+/// the flags (which only come with the default mode).
+fn constructors() -> Vec<(String, Constructor)> {
+    let mut constructors: Vec<(String, Constructor)> = modes()
+        .into_iter()
+        .map(|(name, mode)| {
+            let wrap: Constructor = Box::new(move |val| F32Wrapper::with_mode(val, mode));
+            (name.to_string(), wrap)
+        })
+        .collect();
+    constructors.push((
+        "distinct zeros".to_string(),
+        Box::new(F32Wrapper::with_distinct_zeros),
+    ));
+    constructors.push((
+        "sign ignored".to_string(),
+        Box::new(F32Wrapper::with_sign_ignored),
+    ));
+    constructors
+}
+
+/// Equal items hash alike, and equality is symmetric, over every pair.
+fn assert_consistent<T: Hash + Eq + Debug>(items: &[T]) {
+    for a in items {
+        for b in items {
+            let (forwards, backwards) = (a == b, b == a);
+            assert!(forwards == backwards, "{a:?} and {b:?} aren't symmetric");
+            assert!(
+                a != b || hash_of(a) == hash_of(b),
+                "{a:?} == {b:?} but they hash differently"
+            );
+        }
+    }
+}
+
+#[test]
+fn it_hashes_equal_wrappers_alike_across_modes_and_flags() {
+    let values = tricky_values();
+    let constructors = constructors();
+    for (name_a, wrap_a) in &constructors {
+        for (name_b, wrap_b) in &constructors {
+            for a in &values {
+                for b in &values {
+                    let (a, b) = (wrap_a(*a), wrap_b(*b));
+                    assert!(
+                        a != b || hash_of(a) == hash_of(b),
+                        "{name_a} vs {name_b}: {a:?} == {b:?} but they hash differently"
+                    );
+                    assert!(
+                        (a == b) == (b == a),
+                        "{name_a} vs {name_b}: {a:?} and {b:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn it_keeps_eq_transitive_across_modes_and_flags() {
+    // A value that's close to, or the same bits as, its neighbours under
+    // every construction, so any cross-configuration equality shows up
+    let values = [3.0, -3.0, 3.000001, 0.0, -0.0, f32::NAN];
+    let wrappers: Vec<F32Wrapper> = constructors()
+        .iter()
+        .flat_map(|(_, wrap)| values.map(wrap))
+        .collect();
+    for a in &wrappers {
+        for b in wrappers.iter().filter(|b| a == *b) {
+            for c in wrappers.iter().filter(|c| b == *c) {
+                assert!(a == c, "{a:?} == {b:?} == {c:?}, but not {a:?} == {c:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn it_hashes_equal_canonical_floats_alike_across_canonicalizers() {
+    let values = tricky_values();
+    let items: Vec<CanonicalF32<ComparisonMode>> = modes()
+        .into_iter()
+        .flat_map(|(_, mode)| values.iter().map(move |val| CanonicalF32::new(*val, mode)))
+        .collect();
+    assert_consistent(&items);
+}
+
+#[test]
+fn it_hashes_equal_f64_wrappers_alike_across_tolerances_and_flags() {
+    let values = tricky_values();
+    let mut items = Vec::new();
+    for val in &values {
+        for tolerance in [0.0, 1e-5, 0.005, 1e-4] {
+            items.push(F64Wrapper::with_tolerance(f64::from(*val), tolerance));
+        }
+        for (_, wrap) in constructors() {
+            items.push(F64Wrapper::from(wrap(*val)));
+        }
+    }
+    assert_consistent(&items);
+}
+
+#[test]
+fn it_hashes_equal_signals_and_arrays_alike_across_tolerances() {
+    let values = tricky_values();
+    let tolerances = [0.0, F32_ERROR_TOLERANCE, 0.005, 0.0001];
+    let mut signals = Vec::new();
+    let mut arrays = Vec::new();
+    for pair in values.windows(2) {
+        for tolerance in tolerances {
+            signals.push(SignalWrapper::new(vec![pair[0]], tolerance));
+            signals.push(SignalWrapper::new(pair.to_vec(), tolerance));
+            arrays.push(FloatArrayWrapper::with_tolerance(
+                [pair[0], pair[1]],
+                tolerance,
+            ));
+            arrays.push(FloatArrayWrapper::with_tolerance(
+                [pair[1], pair[0]],
+                tolerance,
+            ));
+        }
+    }
+    assert_consistent(&signals);
+    assert_consistent(&arrays);
+}
@@ -0,0 +1,54 @@
+//! `FloatHashSet` against the naive alternative it's meant to replace: a
+//! `Vec` of representatives, each new value pushed only if nothing already
+//! in it is within tolerance, and lookups scanning the whole thing.
+
+use floaty_hash::FloatHashSet;
+
+const TOLERANCE: f32 = 0.01;
+
+/// A small xorshift, so the data is the same on every run without pulling
+/// in a `rand` dependency.
+fn pseudo_random(count: usize, seed: u64) -> Vec<f32> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            // 24 random bits, spread over [-5, 5)
+            #[allow(clippy::cast_precision_loss)]
+            let unit = (state >> 40) as f32 / (1u64 << 24) as f32;
+            unit * 10.0 - 5.0
+        })
+        .collect()
+}
+
+fn within_tolerance(a: f32, b: f32) -> bool {
+    a.to_bits() == b.to_bits() || (a - b).abs() <= TOLERANCE
+}
+
+#[test]
+fn it_answers_membership_like_a_linear_scan() {
+    for seed in [1, 42, 0xdead_beef] {
+        let values = pseudo_random(2000, seed);
+        let mut set = FloatHashSet::new(TOLERANCE);
+        let mut representatives: Vec<f32> = Vec::new();
+        for val in &values {
+            set.insert(*val);
+            if !representatives
+                .iter()
+                .any(|stored| within_tolerance(*stored, *val))
+            {
+                representatives.push(*val);
+            }
+        }
+
+        assert!(set.len() == representatives.len(), "seed {seed}");
+        for query in pseudo_random(5000, seed.wrapping_mul(31)) {
+            let linear = representatives
+                .iter()
+                .any(|stored| within_tolerance(*stored, query));
+            assert!(set.contains(query) == linear, "seed {seed}, query {query}");
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! The subnormal/normal transition at `f32::MIN_POSITIVE`, where the
+//! exponent field bottoms out and the spacing between floats stops halving.
+//! Absolute tolerances at that scale make grid cells only a handful of ULPs
+//! wide, so these check that values within tolerance across the boundary
+//! still merge, that equal wrappers hash alike, and that the cells neither
+//! collapse nor skip.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use floaty_hash::{canonicalize, snap_to_grid, F32Wrapper, FloatHashSet, Rounding};
+
+/// The smallest subnormal, which is also the gap between every subnormal and
+/// the next, and between `MIN_POSITIVE` and the float above it.
+const STEP: f32 = f32::from_bits(1);
+
+fn tolerances() -> [f32; 5] {
+    [
+        STEP,
+        STEP * 3.0,
+        f32::MIN_POSITIVE / 2.0,
+        f32::MIN_POSITIVE,
+        f32::MIN_POSITIVE * 4.0,
+    ]
+}
+
+/// Values either side of `MIN_POSITIVE`, mirrored below zero.
+fn values_around_the_boundary() -> Vec<f32> {
+    let mut values = Vec::new();
+    let mut below = f32::MIN_POSITIVE;
+    let mut above = f32::MIN_POSITIVE;
+    for _ in 0..16 {
+        values.extend([below, above]);
+        below = below.next_down();
+        above = above.next_up();
+    }
+    values.extend([f32::MIN_POSITIVE / 2.0, f32::MIN_POSITIVE * 2.0, STEP, 0.0]);
+    let negated: Vec<f32> = values.iter().map(|val| -val).collect();
+    values.extend(negated);
+    values
+}
+
+fn hash_of(val: F32Wrapper) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    val.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn it_hashes_equal_wrappers_alike_across_the_boundary() {
+    let values = values_around_the_boundary();
+    for tolerance in tolerances() {
+        for a in &values {
+            for b in &values {
+                let (a, b) = (
+                    F32Wrapper::with_tolerance(*a, tolerance),
+                    F32Wrapper::with_tolerance(*b, tolerance),
+                );
+                assert!(
+                    a != b || hash_of(a) == hash_of(b),
+                    "{:e} and {:e} are equal at tolerance {tolerance:e} but hash apart",
+                    a.value(),
+                    b.value(),
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn it_merges_subnormals_with_normals_within_tolerance() {
+    for tolerance in tolerances() {
+        for normal in [f32::MIN_POSITIVE, f32::MIN_POSITIVE.next_up()] {
+            // The nearest few subnormals, and the furthest ones still in range
+            let furthest = (normal - tolerance).max(STEP);
+            let subnormals = (1..=8u8)
+                .map(|steps| normal - f32::from(steps) * STEP)
+                .filter(|subnormal| normal - subnormal <= tolerance)
+                .chain([furthest, furthest.next_up()])
+                .filter(|subnormal| *subnormal < f32::MIN_POSITIVE);
+            for subnormal in subnormals {
+                for sign in [1.0, -1.0] {
+                    let mut set = FloatHashSet::new(tolerance);
+                    set.insert(sign * normal);
+                    set.insert(sign * subnormal);
+                    assert!(
+                        set.len() == 1,
+                        "{:e} and {:e} didn't merge at tolerance {tolerance:e}",
+                        sign * normal,
+                        sign * subnormal,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn it_keeps_subnormals_further_apart_than_tolerance_distinct() {
+    for tolerance in tolerances() {
+        let mut set = FloatHashSet::new(tolerance);
+        set.insert(f32::MIN_POSITIVE);
+        let beyond = f32::MIN_POSITIVE - tolerance;
+        assert!(set.contains(beyond));
+        assert!(!set.contains(beyond.next_down()));
+        assert!(!set.contains((f32::MIN_POSITIVE + tolerance).next_up()));
+    }
+}
+
+#[test]
+fn it_gives_each_step_its_own_cell_at_a_one_step_tolerance() {
+    let mut val = f32::MIN_POSITIVE;
+    for _ in 0..8 {
+        val = val.next_down();
+    }
+    let mut previous = canonicalize(val, STEP).0;
+    for _ in 0..16 {
+        val = val.next_up();
+        let (cell, residual) = canonicalize(val, STEP);
+        assert!(cell == previous + 1, "{val:e} skipped or repeated a cell");
+        assert!(residual == 0.0);
+        previous = cell;
+    }
+}
+
+#[test]
+fn it_snaps_onto_cells_without_leaving_them() {
+    for tolerance in tolerances() {
+        for val in values_around_the_boundary() {
+            let snapped = snap_to_grid(val, tolerance, Rounding::default());
+            assert!(
+                canonicalize(snapped, tolerance).0 == canonicalize(val, tolerance).0,
+                "{val:e} snapped out of its cell at tolerance {tolerance:e}",
+            );
+            assert!(snap_to_grid(snapped, tolerance, Rounding::default()) == snapped);
+            assert!(
+                F32Wrapper::with_tolerance(snapped, tolerance)
+                    == F32Wrapper::with_tolerance(val, tolerance)
+            );
+            let (_, residual) = canonicalize(val, tolerance);
+            assert!(residual.abs() <= tolerance / 2.0);
+        }
+    }
+}